@@ -0,0 +1,51 @@
+use geng::prelude::*;
+use geng_egui::*;
+
+struct Game {
+    clicks: usize,
+    counter: u32,
+}
+
+impl geng::State for Game {
+    fn handle_event(&mut self, event: geng::Event) {
+        // Only receives events that the ui did not want
+        if let geng::Event::MousePress { .. } = event {
+            self.clicks += 1;
+        }
+    }
+
+    fn draw(&mut self, framebuffer: &mut ugli::Framebuffer) {
+        ugli::clear(framebuffer, Some(Rgba::GRAY), None, None);
+    }
+}
+
+impl EguiState for Game {
+    fn egui(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Egui Window").show(ctx, |ui| {
+            ui.label(format!("Clicks outside the ui: {}", self.clicks));
+            if ui.button("Increment").clicked() {
+                self.counter += 1;
+            }
+            ui.label(format!("Counter: {}", self.counter));
+        });
+    }
+}
+
+fn main() {
+    logger::init();
+    geng::setup_panic_handler();
+
+    let mut options = geng::ContextOptions::default();
+    options.window.title = "Egui State Example".to_string();
+
+    Geng::run_with(&options, move |geng| async move {
+        let state = WithEgui::new(
+            &geng,
+            Game {
+                clicks: 0,
+                counter: 0,
+            },
+        );
+        geng.run_state(state).await;
+    });
+}
@@ -0,0 +1,217 @@
+use super::*;
+
+use anyhow::Result;
+use image::AnimationDecoder;
+
+/// A frame of an [AnimatedIcon].
+#[derive(Clone)]
+pub struct AnimationFrame {
+    pub icon: AtlasIcon,
+    /// How long the frame is shown, in seconds.
+    pub duration: f64,
+}
+
+/// An icon cycling through a sequence of frames.
+///
+/// The animation follows egui time ([egui::InputState::time])
+/// and requests a repaint whenever the next frame is due.
+pub struct AnimatedIcon {
+    frames: Vec<AnimationFrame>,
+    /// Whether to start over after the last frame instead of stopping on it.
+    pub looping: bool,
+    state: PlayState,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum PlayState {
+    /// Playing since the given egui time, or since the next time it is shown.
+    Playing {
+        start: Option<f64>,
+    },
+    Paused {
+        elapsed: f64,
+    },
+}
+
+impl AnimatedIcon {
+    /// Frames are played in order. Starts playing and looping.
+    /// Fails if there are no frames.
+    pub fn new(frames: Vec<AnimationFrame>) -> Result<Self> {
+        anyhow::ensure!(
+            !frames.is_empty(),
+            "An animated icon must have at least one frame"
+        );
+        Ok(Self {
+            frames,
+            looping: true,
+            state: PlayState::Playing { start: None },
+        })
+    }
+
+    /// Frames of the same duration.
+    pub fn from_icons(
+        icons: impl IntoIterator<Item = AtlasIcon>,
+        frame_duration: f64,
+    ) -> Result<Self> {
+        Self::new(
+            icons
+                .into_iter()
+                .map(|icon| AnimationFrame {
+                    icon,
+                    duration: frame_duration,
+                })
+                .collect(),
+        )
+    }
+
+    /// A strip of frames of the same size laid out left to right.
+    pub fn from_strip(icon: SharedIcon, frame_count: usize, frame_duration: f64) -> Result<Self> {
+        let frame_size = vec2(icon.size().x / frame_count.max(1), icon.size().y);
+        let icons = (0..frame_count).map(|index| {
+            let min = vec2(index * frame_size.x, 0);
+            AtlasIcon::region(icon.clone(), Aabb2::from_corners(min, min + frame_size))
+        });
+        Self::from_icons(icons, frame_duration)
+    }
+
+    /// All frames of an atlas, in order.
+    pub fn from_atlas(atlas: &Atlas, frame_duration: f64) -> Result<Self> {
+        Self::from_icons(atlas.frames().iter().cloned(), frame_duration)
+    }
+
+    /// Decode an animated GIF or PNG (APNG), keeping the frame delays.
+    /// Any other image is decoded as a single frame.
+    /// Like for [IconImage], `premultiply_alpha` is ignored since image files have straight alpha.
+    pub fn from_bytes(
+        data: &[u8],
+        alloc: &egui::Context,
+        options: &geng::asset::TextureOptions,
+    ) -> Result<Self> {
+        let still = || -> Result<Self> {
+            let image = IconImage::from_bytes(data, options)?;
+            let icon = Icon::from_image(&image, alloc).into_shared();
+            Self::from_icons([AtlasIcon::whole(icon)], 1.0)
+        };
+        let reader = std::io::Cursor::new(data);
+        let frames = match image::guess_format(data)? {
+            image::ImageFormat::Gif => image::codecs::gif::GifDecoder::new(reader)?
+                .into_frames()
+                .collect_frames()?,
+            image::ImageFormat::Png => {
+                let decoder = image::codecs::png::PngDecoder::new(reader)?;
+                if decoder.is_apng()? {
+                    decoder.apng()?.into_frames().collect_frames()?
+                } else {
+                    return still();
+                }
+            }
+            _ => return still(),
+        };
+        let frames = frames
+            .into_iter()
+            .enumerate()
+            .map(|(index, frame)| {
+                let (numer, denom) = frame.delay().numer_denom_ms();
+                let mut duration = numer as f64 / denom as f64 / 1000.0;
+                // Like browsers, treat (almost) zero delays as unspecified
+                if duration <= 0.01 {
+                    duration = 0.1;
+                }
+                let buffer = frame.into_buffer();
+                let size = vec2(buffer.width(), buffer.height()).map(|x| x as usize);
+                let image = IconImage::from_raw(size, buffer.as_raw(), &straight_alpha(options))
+                    .with_name(format!("Animation frame {index}"));
+                AnimationFrame {
+                    icon: AtlasIcon::whole(Icon::from_image(&image, alloc).into_shared()),
+                    duration,
+                }
+            })
+            .collect();
+        Self::new(frames)
+    }
+
+    pub fn frames(&self) -> &[AnimationFrame] {
+        &self.frames
+    }
+
+    /// Length of one pass through the frames, in seconds.
+    pub fn duration(&self) -> f64 {
+        self.frames.iter().map(|frame| frame.duration).sum()
+    }
+
+    pub fn is_playing(&self) -> bool {
+        matches!(self.state, PlayState::Playing { .. })
+    }
+
+    /// Continue playing from where the animation was paused.
+    pub fn play(&mut self, ctx: &egui::Context) {
+        if let PlayState::Paused { elapsed } = self.state {
+            let now = ctx.input(|input| input.time);
+            self.state = PlayState::Playing {
+                start: Some(now - elapsed),
+            };
+        }
+    }
+
+    pub fn pause(&mut self, ctx: &egui::Context) {
+        let elapsed = self.elapsed(ctx.input(|input| input.time));
+        self.state = PlayState::Paused { elapsed };
+    }
+
+    /// Play from the first frame, starting the next time the icon is shown.
+    pub fn restart(&mut self) {
+        self.state = PlayState::Playing { start: None };
+    }
+
+    /// Seconds since the animation has started, not counting pauses.
+    fn elapsed(&self, now: f64) -> f64 {
+        match self.state {
+            PlayState::Playing { start } => start.map_or(0.0, |start| now - start),
+            PlayState::Paused { elapsed } => elapsed,
+        }
+    }
+
+    /// Index of the frame shown after the given time, and how long until it changes.
+    fn frame_index(&self, elapsed: f64) -> (usize, Option<f64>) {
+        let duration = self.duration();
+        let mut time = if self.looping && duration > 0.0 {
+            elapsed.rem_euclid(duration)
+        } else {
+            elapsed
+        };
+        for (index, frame) in self.frames.iter().enumerate() {
+            if time < frame.duration {
+                return (index, Some(frame.duration - time));
+            }
+            time -= frame.duration;
+        }
+        (self.frames.len().saturating_sub(1), None)
+    }
+
+    /// The frame to show now.
+    /// Requests a repaint for when the next frame is due while the animation is playing.
+    pub fn current(&mut self, ctx: &egui::Context) -> &AtlasIcon {
+        let now = ctx.input(|input| input.time);
+        if let PlayState::Playing { start: None } = self.state {
+            self.state = PlayState::Playing { start: Some(now) };
+        }
+        let (index, remaining) = self.frame_index(self.elapsed(now));
+        if let (true, Some(remaining)) = (self.is_playing(), remaining) {
+            // An infinite duration holds the frame, so there is nothing to wait for
+            if let Ok(remaining) = std::time::Duration::try_from_secs_f64(remaining) {
+                ctx.request_repaint_after(remaining);
+            }
+        }
+        &self.frames[index].icon
+    }
+
+    /// An image widget showing the current frame, by default at its size in pixels.
+    pub fn image(&mut self, ctx: &egui::Context) -> egui::Image<'static> {
+        self.current(ctx).image()
+    }
+
+    /// Show the current frame.
+    pub fn show(&mut self, ui: &mut egui::Ui) -> egui::Response {
+        ui.add(self.image(ui.ctx()))
+    }
+}
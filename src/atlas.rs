@@ -0,0 +1,279 @@
+use super::*;
+
+use anyhow::Result;
+
+/// A part of an icon, e.g. a glyph of a sprite sheet.
+#[derive(Clone)]
+pub struct AtlasIcon {
+    pub icon: SharedIcon,
+    /// The part of the icon in texture coordinates (`0..1` with the origin at the top-left corner).
+    pub uv: egui::Rect,
+    /// Size of the part in pixels.
+    pub size: vec2<usize>,
+}
+
+impl AtlasIcon {
+    /// The whole icon.
+    pub fn whole(icon: SharedIcon) -> Self {
+        Self {
+            size: icon.size(),
+            icon,
+            uv: egui::Rect::from_min_max(egui::Pos2::ZERO, egui::pos2(1.0, 1.0)),
+        }
+    }
+
+    /// A part of the icon given in pixels, with the origin at the top-left corner.
+    pub fn region(icon: SharedIcon, region: Aabb2<usize>) -> Self {
+        let icon_size = icon.size().map(|x| x as f32);
+        let uv =
+            |pos: vec2<usize>| egui::pos2(pos.x as f32 / icon_size.x, pos.y as f32 / icon_size.y);
+        Self {
+            uv: egui::Rect::from_min_max(uv(region.min), uv(region.max)),
+            size: region.size(),
+            icon,
+        }
+    }
+
+    pub fn sized_texture(&self) -> egui::load::SizedTexture {
+        egui::load::SizedTexture::new(
+            self.icon.id(),
+            egui::vec2(self.size.x as f32, self.size.y as f32),
+        )
+    }
+
+    /// An image widget showing the icon, by default at its size in pixels.
+    pub fn image(&self) -> egui::Image<'static> {
+        egui::Image::new(self.sized_texture()).uv(self.uv)
+    }
+
+    /// A button showing the icon.
+    pub fn image_button(&self) -> egui::ImageButton<'static> {
+        egui::ImageButton::new(self.image())
+    }
+
+    /// Paint the icon stretched over the rect.
+    pub fn paint(&self, painter: &egui::Painter, rect: egui::Rect, tint: egui::Color32) {
+        painter.image(self.icon.id(), rect, self.uv, tint);
+    }
+}
+
+/// How an atlas image is divided into frames.
+///
+/// Loadable from JSON or TOML (depending on the file extension), e.g.:
+/// ```toml
+/// [grid]
+/// cell_size = [16, 16]
+/// ```
+/// or
+/// ```toml
+/// [frames.sword]
+/// position = [0, 0]
+/// size = [16, 32]
+/// ```
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AtlasFrames {
+    /// Cells of the same size, named by their index (`"0"`, `"1"`, ...) in row-major order.
+    Grid {
+        cell_size: [usize; 2],
+        /// Gap between the cells in pixels.
+        #[serde(default)]
+        spacing: [usize; 2],
+    },
+    /// Named frames.
+    Frames(BTreeMap<String, AtlasFrame>),
+}
+
+/// A frame of an atlas, in pixels with the origin at the top-left corner.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct AtlasFrame {
+    pub position: [usize; 2],
+    pub size: [usize; 2],
+}
+
+impl AtlasFrames {
+    pub fn from_json(text: &str) -> Result<Self> {
+        Ok(serde_json::from_str(text)?)
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    /// Frames as regions of an image of the given size, in order.
+    /// Frames that do not fit in the image are skipped.
+    fn regions(&self, image_size: vec2<usize>) -> Vec<(String, Aabb2<usize>)> {
+        match self {
+            Self::Grid { cell_size, spacing } => {
+                let cell_size = vec2(cell_size[0], cell_size[1]);
+                let step = cell_size + vec2(spacing[0], spacing[1]);
+                if cell_size.x == 0 || cell_size.y == 0 {
+                    return Vec::new();
+                }
+                let columns = (image_size.x + spacing[0]) / step.x;
+                let rows = (image_size.y + spacing[1]) / step.y;
+                (0..rows)
+                    .flat_map(|row| (0..columns).map(move |column| vec2(column, row)))
+                    .enumerate()
+                    .map(|(index, cell)| {
+                        let min = vec2(cell.x * step.x, cell.y * step.y);
+                        (index.to_string(), Aabb2::from_corners(min, min + cell_size))
+                    })
+                    .collect()
+            }
+            Self::Frames(frames) => frames
+                .iter()
+                .filter_map(|(name, frame)| {
+                    let min = vec2(frame.position[0], frame.position[1]);
+                    let max = min + vec2(frame.size[0], frame.size[1]);
+                    if max.x > image_size.x || max.y > image_size.y {
+                        log::error!(
+                            "Atlas frame {name:?} ({}x{} at {}, {}) does not fit in the {}x{} image",
+                            frame.size[0],
+                            frame.size[1],
+                            min.x,
+                            min.y,
+                            image_size.x,
+                            image_size.y,
+                        );
+                        return None;
+                    }
+                    Some((name.clone(), Aabb2::from_corners(min, max)))
+                })
+                .collect(),
+        }
+    }
+}
+
+impl geng::asset::Load for AtlasFrames {
+    type Options = ();
+    fn load(
+        _manager: &geng::asset::Manager,
+        path: &std::path::Path,
+        _options: &Self::Options,
+    ) -> geng::asset::Future<Self> {
+        let path = path.to_owned();
+        async move {
+            let data = file::load_bytes(&path).await?;
+            let text = std::str::from_utf8(&data)?;
+            match path.extension().and_then(|ext| ext.to_str()) {
+                Some("toml") => Self::from_toml(text),
+                _ => Self::from_json(text),
+            }
+        }
+        .boxed_local()
+    }
+    const DEFAULT_EXT: Option<&'static str> = Some("json");
+}
+
+/// A sprite sheet: one texture divided into many icons.
+pub struct Atlas {
+    frames: Vec<AtlasIcon>,
+    names: HashMap<String, usize>,
+}
+
+impl Atlas {
+    /// Named frames that do not fit in the icon are left out, with an error logged.
+    pub fn new(icon: SharedIcon, frames: &AtlasFrames) -> Self {
+        let mut names = HashMap::new();
+        let frames = frames
+            .regions(icon.size())
+            .into_iter()
+            .enumerate()
+            .map(|(index, (name, region))| {
+                names.insert(name, index);
+                AtlasIcon::region(icon.clone(), region)
+            })
+            .collect();
+        Self { frames, names }
+    }
+
+    /// Create the atlas texture from loaded image data.
+    pub fn from_image(image: &IconImage, frames: &AtlasFrames, alloc: &egui::Context) -> Self {
+        Self::new(Icon::from_image(image, alloc).into_shared(), frames)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AtlasIcon> {
+        self.names.get(name).map(|&index| &self.frames[index])
+    }
+
+    /// All frames, in the order of the grid or sorted by name.
+    pub fn frames(&self) -> &[AtlasIcon] {
+        &self.frames
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(min: (usize, usize), max: (usize, usize)) -> Aabb2<usize> {
+        Aabb2::from_corners(vec2(min.0, min.1), vec2(max.0, max.1))
+    }
+
+    #[test]
+    fn grid_regions() {
+        let frames = AtlasFrames::Grid {
+            cell_size: [16, 16],
+            spacing: [0, 0],
+        };
+        // The partial cells at the right and bottom edges are left out
+        let regions = frames.regions(vec2(40, 20));
+        assert_eq!(
+            regions,
+            vec![
+                ("0".to_owned(), region((0, 0), (16, 16))),
+                ("1".to_owned(), region((16, 0), (32, 16))),
+            ]
+        );
+    }
+
+    #[test]
+    fn grid_regions_with_spacing() {
+        let frames = AtlasFrames::Grid {
+            cell_size: [16, 8],
+            spacing: [1, 2],
+        };
+        // The last column and row need no spacing after them
+        let regions = frames.regions(vec2(50, 18));
+        let names: Vec<&str> = regions.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["0", "1", "2", "3", "4", "5"]);
+        assert_eq!(regions[0].1, region((0, 0), (16, 8)));
+        assert_eq!(regions[2].1, region((34, 0), (50, 8)));
+        assert_eq!(regions[4].1, region((17, 10), (33, 18)));
+    }
+
+    #[test]
+    fn grid_regions_empty_cells() {
+        let frames = AtlasFrames::Grid {
+            cell_size: [0, 16],
+            spacing: [0, 0],
+        };
+        assert!(frames.regions(vec2(32, 32)).is_empty());
+
+        let frames = AtlasFrames::Grid {
+            cell_size: [64, 64],
+            spacing: [0, 0],
+        };
+        assert!(frames.regions(vec2(32, 32)).is_empty());
+    }
+
+    #[test]
+    fn named_regions_out_of_bounds() {
+        let frames = AtlasFrames::from_toml(
+            r#"
+            [frames.inside]
+            position = [16, 0]
+            size = [16, 32]
+            [frames.outside]
+            position = [24, 0]
+            size = [16, 16]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            frames.regions(vec2(32, 32)),
+            vec![("inside".to_owned(), region((16, 0), (32, 32)))]
+        );
+    }
+}
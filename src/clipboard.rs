@@ -0,0 +1,170 @@
+use super::*;
+
+/// A clipboard used to copy and paste text in egui widgets.
+pub trait Clipboard {
+    /// Get the current text contents, if any.
+    fn get(&mut self) -> Option<String>;
+    /// Replace the contents with the given text.
+    fn set(&mut self, text: String);
+}
+
+/// A clipboard that only exists inside the application.
+///
+/// Used by default on the web, and useful in tests.
+/// All clones hold the same text.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryClipboard {
+    text: Rc<RefCell<Option<String>>>,
+}
+
+impl MemoryClipboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(&self) -> Option<String> {
+        self.text.borrow().clone()
+    }
+}
+
+impl Clipboard for MemoryClipboard {
+    fn get(&mut self) -> Option<String> {
+        self.text()
+    }
+
+    fn set(&mut self, text: String) {
+        *self.text.borrow_mut() = Some(text);
+    }
+}
+
+/// The operating system clipboard.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct SystemClipboard {
+    inner: Option<arboard::Clipboard>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl SystemClipboard {
+    pub fn new() -> Self {
+        let inner = match arboard::Clipboard::new() {
+            Ok(clipboard) => Some(clipboard),
+            Err(err) => {
+                log::error!("Failed to access the system clipboard: {err}");
+                None
+            }
+        };
+        Self { inner }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for SystemClipboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Clipboard for SystemClipboard {
+    fn get(&mut self) -> Option<String> {
+        let clipboard = self.inner.as_mut()?;
+        match clipboard.get_text() {
+            Ok(text) => Some(text),
+            Err(err) => {
+                log::error!("Failed to read the system clipboard: {err}");
+                None
+            }
+        }
+    }
+
+    fn set(&mut self, text: String) {
+        let Some(clipboard) = self.inner.as_mut() else {
+            return;
+        };
+        if let Err(err) = clipboard.set_text(text) {
+            log::error!("Failed to write to the system clipboard: {err}");
+        }
+    }
+}
+
+/// The clipboard used by default on the current platform.
+///
+/// On the web the browser clipboard can only be read asynchronously (or from a paste event),
+/// which doesn't fit egui's synchronous [egui::Event::Paste], so it is not used.
+pub(crate) fn default_clipboard() -> Box<dyn Clipboard> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        Box::new(SystemClipboard::new())
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        Box::new(MemoryClipboard::new())
+    }
+}
+
+/// The clipboard event for a key pressed with the given modifiers, if it is a clipboard shortcut.
+pub(crate) fn shortcut_event(
+    key: egui::Key,
+    modifiers: egui::Modifiers,
+    clipboard: &mut dyn Clipboard,
+) -> Option<egui::Event> {
+    if !modifiers.command {
+        return None;
+    }
+    match key {
+        egui::Key::C => Some(egui::Event::Copy),
+        egui::Key::X => Some(egui::Event::Cut),
+        egui::Key::V => clipboard.get().map(egui::Event::Paste),
+        _ => None,
+    }
+}
+
+/// Put the text egui copied during the frame into the clipboard.
+pub(crate) fn store_copied_text(clipboard: &mut dyn Clipboard, copied_text: String) {
+    if !copied_text.is_empty() {
+        clipboard.set(copied_text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shortcuts() {
+        let mut clipboard = MemoryClipboard::new();
+        let command = egui::Modifiers::COMMAND;
+        assert_eq!(
+            shortcut_event(egui::Key::C, command, &mut clipboard),
+            Some(egui::Event::Copy)
+        );
+        assert_eq!(
+            shortcut_event(egui::Key::X, command, &mut clipboard),
+            Some(egui::Event::Cut)
+        );
+
+        // Nothing to paste yet
+        assert_eq!(shortcut_event(egui::Key::V, command, &mut clipboard), None);
+        clipboard.set("pasted".to_owned());
+        assert_eq!(
+            shortcut_event(egui::Key::V, command, &mut clipboard),
+            Some(egui::Event::Paste("pasted".to_owned()))
+        );
+
+        // Not shortcuts
+        assert_eq!(
+            shortcut_event(egui::Key::C, egui::Modifiers::NONE, &mut clipboard),
+            None
+        );
+        assert_eq!(shortcut_event(egui::Key::A, command, &mut clipboard), None);
+    }
+
+    #[test]
+    fn copied_text() {
+        let mut clipboard = MemoryClipboard::new();
+        store_copied_text(&mut clipboard, String::new());
+        assert_eq!(clipboard.text(), None);
+        store_copied_text(&mut clipboard, "copied".to_owned());
+        assert_eq!(clipboard.text(), Some("copied".to_owned()));
+    }
+}
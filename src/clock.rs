@@ -0,0 +1,112 @@
+use super::*;
+
+/// A source of time for egui animations, tooltips and double-click detection.
+pub trait Clock {
+    /// Time in seconds since some fixed point in the past.
+    fn now(&mut self) -> f64;
+}
+
+/// Measures real time since creation.
+pub struct SystemClock {
+    timer: Timer,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self {
+            timer: Timer::new(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&mut self) -> f64 {
+        self.timer.elapsed().as_secs_f64()
+    }
+}
+
+/// A clock that only advances when told to, useful for deterministic tests.
+///
+/// Time is set with [Self::set] or [Self::advance] on any clone.
+#[derive(Debug, Default, Clone)]
+pub struct ManualClock {
+    time: Rc<Cell<f64>>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&self, time: f64) {
+        self.time.set(time);
+    }
+
+    pub fn advance(&self, delta_time: f64) {
+        self.time.set(self.time.get() + delta_time);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&mut self) -> f64 {
+        self.time.get()
+    }
+}
+
+/// Fill the frame time of the input from the clock.
+pub(crate) fn update_input_time(
+    clock: &mut dyn Clock,
+    last_frame_time: &mut Option<f64>,
+    input: &mut egui::RawInput,
+) {
+    let time = clock.now();
+    if let Some(last_time) = *last_frame_time {
+        let delta_time = (time - last_time) as f32;
+        if delta_time > 0.0 {
+            input.predicted_dt = delta_time;
+        }
+    }
+    *last_frame_time = Some(time);
+    input.time = Some(time);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manual_clock_drives_input() {
+        let clock = ManualClock::new();
+        clock.set(10.0);
+        let mut source = clock.clone();
+        let mut last_frame_time = None;
+        let mut input = egui::RawInput::default();
+        let default_dt = input.predicted_dt;
+
+        // The first frame has nothing to measure
+        update_input_time(&mut source, &mut last_frame_time, &mut input);
+        assert_eq!(input.time, Some(10.0));
+        assert_eq!(input.predicted_dt, default_dt);
+
+        clock.advance(0.25);
+        update_input_time(&mut source, &mut last_frame_time, &mut input);
+        assert_eq!(input.time, Some(10.25));
+        assert_eq!(input.predicted_dt, 0.25);
+
+        // A frame without time passing keeps the last prediction
+        update_input_time(&mut source, &mut last_frame_time, &mut input);
+        assert_eq!(input.time, Some(10.25));
+        assert_eq!(input.predicted_dt, 0.25);
+
+        clock.advance(0.5);
+        update_input_time(&mut source, &mut last_frame_time, &mut input);
+        assert_eq!(input.time, Some(10.75));
+        assert_eq!(input.predicted_dt, 0.5);
+    }
+}
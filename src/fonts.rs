@@ -0,0 +1,123 @@
+use super::*;
+
+/// A font file (TTF/OTF) for egui, loadable as a geng asset.
+#[derive(Clone)]
+pub struct EguiFont {
+    data: egui::FontData,
+}
+
+impl EguiFont {
+    pub fn from_bytes(data: Vec<u8>) -> Self {
+        Self {
+            data: egui::FontData::from_owned(data),
+        }
+    }
+
+    /// Adjust the size and position of the glyphs, e.g. to match the other fonts of a family.
+    pub fn tweak(self, tweak: egui::FontTweak) -> Self {
+        Self {
+            data: self.data.tweak(tweak),
+        }
+    }
+}
+
+impl geng::asset::Load for EguiFont {
+    type Options = ();
+    fn load(
+        _manager: &geng::asset::Manager,
+        path: &std::path::Path,
+        _options: &Self::Options,
+    ) -> geng::asset::Future<Self> {
+        let path = path.to_owned();
+        async move {
+            let data = file::load_bytes(&path).await?;
+            Ok(Self::from_bytes(data))
+        }
+        .boxed_local()
+    }
+    const DEFAULT_EXT: Option<&'static str> = Some("ttf");
+}
+
+/// Builds egui font definitions out of game fonts.
+///
+/// Each family (e.g. [egui::FontFamily::Proportional]) is a list of fonts:
+/// glyphs missing from the first font are looked up in the following ones.
+/// Use that to add fallbacks for CJK or emoji.
+///
+/// ```ignore
+/// Fonts::new()
+///     .add("game", assets.font.clone())
+///     .add("cjk", assets.cjk_font.clone())
+///     .primary(egui::FontFamily::Proportional, "game")
+///     .fallback(egui::FontFamily::Proportional, "cjk")
+///     .install(egui.get_context());
+/// ```
+pub struct Fonts {
+    definitions: egui::FontDefinitions,
+}
+
+impl Fonts {
+    /// Start with egui's default fonts, which then serve as fallbacks.
+    pub fn new() -> Self {
+        Self {
+            definitions: egui::FontDefinitions::default(),
+        }
+    }
+
+    /// Start without any fonts.
+    pub fn empty() -> Self {
+        Self {
+            definitions: egui::FontDefinitions::empty(),
+        }
+    }
+
+    /// Make a font available under the given name.
+    pub fn add(mut self, name: impl Into<String>, font: EguiFont) -> Self {
+        self.definitions.font_data.insert(name.into(), font.data);
+        self
+    }
+
+    /// Use the font before all other fonts of the family.
+    pub fn primary(mut self, family: egui::FontFamily, name: impl Into<String>) -> Self {
+        self.definitions
+            .families
+            .entry(family)
+            .or_default()
+            .insert(0, name.into());
+        self
+    }
+
+    /// Use the font after all other fonts of the family.
+    pub fn fallback(mut self, family: egui::FontFamily, name: impl Into<String>) -> Self {
+        self.definitions
+            .families
+            .entry(family)
+            .or_default()
+            .push(name.into());
+        self
+    }
+
+    /// Add a custom family, usable with [egui::FontFamily::Name], made of the given fonts in order.
+    pub fn family(mut self, family: impl Into<String>, names: &[&str]) -> Self {
+        self.definitions.families.insert(
+            egui::FontFamily::Name(family.into().into()),
+            names.iter().map(|name| name.to_string()).collect(),
+        );
+        self
+    }
+
+    pub fn into_definitions(self) -> egui::FontDefinitions {
+        self.definitions
+    }
+
+    /// Replace the fonts used by the context. Takes effect on the next frame.
+    pub fn install(self, ctx: &egui::Context) {
+        ctx.set_fonts(self.definitions);
+    }
+}
+
+impl Default for Fonts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -3,42 +3,162 @@ use super::*;
 use anyhow::Result;
 use egui::{ColorImage, Context, ImageData, TextureId, TextureOptions};
 
+/// A texture displayable in egui. The texture is freed when the icon is dropped.
 pub struct Icon {
-    pub texture: TextureId,
-    pub size: vec2<usize>,
+    texture: TextureId,
+    /// Size in pixels, changed by [Icon::set_image].
+    size: Cell<vec2<usize>>,
+    owner: TextureOwner,
+}
+
+/// An icon that can be cheaply cloned and shared between widgets.
+pub type SharedIcon = Rc<Icon>;
+
+/// Where the texture of an [Icon] was allocated.
+enum TextureOwner {
+    Managed(Context),
+    User(UserTextures),
+}
+
+/// Decoded image data for an [Icon], loadable as a geng asset (PNG, JPEG, etc.).
+///
+/// egui needs a [Context] to create textures, so turn it into an icon with [Icon::from_image].
+/// Image files store straight alpha, so loaded colors are always premultiplied,
+/// even with the default [geng::asset::TextureOptions] where `premultiply_alpha` is unset.
+pub struct IconImage {
+    /// Name shown when debugging egui textures.
+    name: String,
+    image: ColorImage,
+    options: TextureOptions,
+}
+
+impl IconImage {
+    /// See [Icon::from_raw] for the meaning of the options.
+    pub fn from_raw(size: vec2<usize>, data: &[u8], options: &geng::asset::TextureOptions) -> Self {
+        let image = if options.premultiply_alpha {
+            ColorImage::from_rgba_unmultiplied(**size, data)
+        } else {
+            ColorImage::from_rgba_premultiplied(**size, data)
+        };
+        Self {
+            name: format!("Icon {}x{}", size.x, size.y),
+            image,
+            options: texture_options(options),
+        }
+    }
+
+    /// Set the name shown when debugging egui textures.
+    pub fn with_name(self, name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..self
+        }
+    }
+
+    /// Decode an image file. `premultiply_alpha` is ignored, see [IconImage].
+    pub fn from_bytes(data: &[u8], options: &geng::asset::TextureOptions) -> Result<Self> {
+        let image = image::load_from_memory(data)?.to_rgba8();
+        let size = vec2(image.width(), image.height()).map(|x| x as usize);
+        Ok(Self::from_raw(
+            size,
+            image.as_raw(),
+            &straight_alpha(options),
+        ))
+    }
+
+    pub fn size(&self) -> vec2<usize> {
+        let [width, height] = self.image.size;
+        vec2(width, height)
+    }
+}
+
+impl geng::asset::Load for IconImage {
+    type Options = geng::asset::TextureOptions;
+    fn load(
+        _manager: &geng::asset::Manager,
+        path: &std::path::Path,
+        options: &Self::Options,
+    ) -> geng::asset::Future<Self> {
+        let path = path.to_owned();
+        let options = options.clone();
+        async move {
+            let data = file::load_bytes(&path).await?;
+            let image = Self::from_bytes(&data, &options)?;
+            Ok(image.with_name(path.to_string_lossy()))
+        }
+        .boxed_local()
+    }
+    const DEFAULT_EXT: Option<&'static str> = Some("png");
 }
 
 impl Icon {
+    /// Create an icon from RGBA pixels, row by row from the top.
+    ///
+    /// Like in geng, `premultiply_alpha` tells whether the colors should be multiplied by alpha:
+    /// set it for images with straight alpha (which includes most image files),
+    /// and leave it unset for images that already are premultiplied.
     pub fn from_raw(
         size: vec2<usize>,
         data: &[u8],
         alloc: &Context,
         options: geng::asset::TextureOptions,
     ) -> Result<Self> {
-        let image = ColorImage::from_rgba_unmultiplied(**size, data);
-
-        let filter = match options.filter {
-            ugli::Filter::Nearest => egui::TextureFilter::Nearest,
-            ugli::Filter::Linear => egui::TextureFilter::Linear,
-        };
-        let options = TextureOptions {
-            magnification: filter,
-            minification: filter,
-            wrap_mode: match options.wrap_mode {
-                ugli::WrapMode::Repeat => egui::TextureWrapMode::Repeat,
-                ugli::WrapMode::Clamp => egui::TextureWrapMode::ClampToEdge,
-            },
-        };
+        Ok(Self::from_image(
+            &IconImage::from_raw(size, data, &options),
+            alloc,
+        ))
+    }
 
+    /// Create an icon from loaded image data.
+    pub fn from_image(image: &IconImage, alloc: &Context) -> Self {
         let id = alloc.tex_manager().write().alloc(
-            "Some icon".to_string(),
-            ImageData::Color(Arc::new(image)),
-            options,
+            image.name.clone(),
+            ImageData::Color(Arc::new(image.image.clone())),
+            image.options,
         );
 
-        Ok(Self { texture: id, size })
+        Self {
+            texture: id,
+            size: Cell::new(image.size()),
+            owner: TextureOwner::Managed(alloc.clone()),
+        }
+    }
+
+    /// Replace the pixels of the icon, keeping its id, so shared icons update everywhere.
+    /// Only possible for icons that are not created [from a texture](Icon::from_texture).
+    pub fn set_image(&self, image: &IconImage) {
+        match &self.owner {
+            TextureOwner::Managed(ctx) => {
+                ctx.tex_manager().write().set(
+                    self.texture,
+                    egui::epaint::ImageDelta::full(image.image.clone(), image.options),
+                );
+                self.size.set(image.size());
+            }
+            TextureOwner::User(_) => {
+                log::error!("Cannot set the image of an icon displaying a texture");
+            }
+        }
+    }
+
+    pub fn into_shared(self) -> SharedIcon {
+        Rc::new(self)
     }
 
+    /// Display a live texture without copying it.
+    /// The texture keeps its own filter and wrap mode, and any changes to it show up in the ui.
+    pub fn from_texture(textures: &UserTextures, texture: Rc<ugli::Texture>) -> Self {
+        let size = texture.size();
+        let id = textures.register(texture);
+        Self {
+            texture: id,
+            size: Cell::new(size),
+            owner: TextureOwner::User(textures.clone()),
+        }
+    }
+
+    /// Copy the contents of the texture into a new egui texture.
+    /// To display the texture without copying it, see [Icon::from_texture].
     pub fn from_ugli(ugli: &Ugli, texture: &ugli::Texture, alloc: &Context) -> Result<Self> {
         let size = texture.size();
 
@@ -56,19 +176,60 @@ impl Icon {
             })
             .collect();
 
-        Self::from_raw(
+        let image = IconImage::from_raw(
             size,
             &data,
-            alloc,
-            geng::asset::TextureOptions {
+            &geng::asset::TextureOptions {
                 filter: ugli::Filter::Nearest, // TODO
                 wrap_mode: ugli::WrapMode::Clamp,
-                premultiply_alpha: false,
+                premultiply_alpha: true,
             },
-        )
+        );
+        Ok(Self::from_image(
+            &image.with_name(format!("Texture copy {}x{}", size.x, size.y)),
+            alloc,
+        ))
     }
 
     pub fn id(&self) -> TextureId {
         self.texture
     }
+
+    /// Size in pixels.
+    pub fn size(&self) -> vec2<usize> {
+        self.size.get()
+    }
+}
+
+impl Drop for Icon {
+    fn drop(&mut self) {
+        match &self.owner {
+            TextureOwner::Managed(ctx) => ctx.tex_manager().write().free(self.texture),
+            TextureOwner::User(textures) => textures.free(self.texture),
+        }
+    }
+}
+
+/// The options with `premultiply_alpha` set, for data known to have straight alpha.
+pub(crate) fn straight_alpha(options: &geng::asset::TextureOptions) -> geng::asset::TextureOptions {
+    geng::asset::TextureOptions {
+        premultiply_alpha: true,
+        ..options.clone()
+    }
+}
+
+/// Converts geng texture options to egui ones.
+fn texture_options(options: &geng::asset::TextureOptions) -> TextureOptions {
+    let filter = match options.filter {
+        ugli::Filter::Nearest => egui::TextureFilter::Nearest,
+        ugli::Filter::Linear => egui::TextureFilter::Linear,
+    };
+    TextureOptions {
+        magnification: filter,
+        minification: filter,
+        wrap_mode: match options.wrap_mode {
+            ugli::WrapMode::Repeat => egui::TextureWrapMode::Repeat,
+            ugli::WrapMode::Clamp => egui::TextureWrapMode::ClampToEdge,
+        },
+    }
 }
@@ -0,0 +1,221 @@
+use super::*;
+
+/// Every geng key that has an egui equivalent.
+/// Several geng keys may map to the same egui key (e.g. [geng::Key::Digit1] and [geng::Key::Numpad1]),
+/// in that case the first one is used when converting back from egui.
+const KEYS: &[(geng::Key, egui::Key)] = &[
+    // Commands
+    (geng::Key::Escape, egui::Key::Escape),
+    (geng::Key::Tab, egui::Key::Tab),
+    (geng::Key::Backspace, egui::Key::Backspace),
+    (geng::Key::Enter, egui::Key::Enter),
+    (geng::Key::NumpadEnter, egui::Key::Enter),
+    (geng::Key::Space, egui::Key::Space),
+    (geng::Key::Insert, egui::Key::Insert),
+    (geng::Key::Delete, egui::Key::Delete),
+    (geng::Key::Home, egui::Key::Home),
+    (geng::Key::End, egui::Key::End),
+    (geng::Key::PageUp, egui::Key::PageUp),
+    (geng::Key::PageDown, egui::Key::PageDown),
+    (geng::Key::ArrowLeft, egui::Key::ArrowLeft),
+    (geng::Key::ArrowRight, egui::Key::ArrowRight),
+    (geng::Key::ArrowUp, egui::Key::ArrowUp),
+    (geng::Key::ArrowDown, egui::Key::ArrowDown),
+    // Punctuation
+    (geng::Key::Backquote, egui::Key::Backtick),
+    (geng::Key::Backslash, egui::Key::Backslash),
+    (geng::Key::BracketLeft, egui::Key::OpenBracket),
+    (geng::Key::BracketRight, egui::Key::CloseBracket),
+    (geng::Key::Comma, egui::Key::Comma),
+    (geng::Key::NumpadComma, egui::Key::Comma),
+    (geng::Key::Equal, egui::Key::Equals),
+    (geng::Key::NumpadEqual, egui::Key::Equals),
+    (geng::Key::Minus, egui::Key::Minus),
+    (geng::Key::NumpadSubtract, egui::Key::Minus),
+    (geng::Key::NumpadAdd, egui::Key::Plus),
+    (geng::Key::Period, egui::Key::Period),
+    (geng::Key::NumpadDecimal, egui::Key::Period),
+    (geng::Key::Quote, egui::Key::Quote),
+    (geng::Key::Semicolon, egui::Key::Semicolon),
+    (geng::Key::Slash, egui::Key::Slash),
+    (geng::Key::NumpadDivide, egui::Key::Slash),
+    // Digits
+    (geng::Key::Digit0, egui::Key::Num0),
+    (geng::Key::Digit1, egui::Key::Num1),
+    (geng::Key::Digit2, egui::Key::Num2),
+    (geng::Key::Digit3, egui::Key::Num3),
+    (geng::Key::Digit4, egui::Key::Num4),
+    (geng::Key::Digit5, egui::Key::Num5),
+    (geng::Key::Digit6, egui::Key::Num6),
+    (geng::Key::Digit7, egui::Key::Num7),
+    (geng::Key::Digit8, egui::Key::Num8),
+    (geng::Key::Digit9, egui::Key::Num9),
+    (geng::Key::Numpad0, egui::Key::Num0),
+    (geng::Key::Numpad1, egui::Key::Num1),
+    (geng::Key::Numpad2, egui::Key::Num2),
+    (geng::Key::Numpad3, egui::Key::Num3),
+    (geng::Key::Numpad4, egui::Key::Num4),
+    (geng::Key::Numpad5, egui::Key::Num5),
+    (geng::Key::Numpad6, egui::Key::Num6),
+    (geng::Key::Numpad7, egui::Key::Num7),
+    (geng::Key::Numpad8, egui::Key::Num8),
+    (geng::Key::Numpad9, egui::Key::Num9),
+    // Letters
+    (geng::Key::A, egui::Key::A),
+    (geng::Key::B, egui::Key::B),
+    (geng::Key::C, egui::Key::C),
+    (geng::Key::D, egui::Key::D),
+    (geng::Key::E, egui::Key::E),
+    (geng::Key::F, egui::Key::F),
+    (geng::Key::G, egui::Key::G),
+    (geng::Key::H, egui::Key::H),
+    (geng::Key::I, egui::Key::I),
+    (geng::Key::J, egui::Key::J),
+    (geng::Key::K, egui::Key::K),
+    (geng::Key::L, egui::Key::L),
+    (geng::Key::M, egui::Key::M),
+    (geng::Key::N, egui::Key::N),
+    (geng::Key::O, egui::Key::O),
+    (geng::Key::P, egui::Key::P),
+    (geng::Key::Q, egui::Key::Q),
+    (geng::Key::R, egui::Key::R),
+    (geng::Key::S, egui::Key::S),
+    (geng::Key::T, egui::Key::T),
+    (geng::Key::U, egui::Key::U),
+    (geng::Key::V, egui::Key::V),
+    (geng::Key::W, egui::Key::W),
+    (geng::Key::X, egui::Key::X),
+    (geng::Key::Y, egui::Key::Y),
+    (geng::Key::Z, egui::Key::Z),
+    // Function keys
+    (geng::Key::F1, egui::Key::F1),
+    (geng::Key::F2, egui::Key::F2),
+    (geng::Key::F3, egui::Key::F3),
+    (geng::Key::F4, egui::Key::F4),
+    (geng::Key::F5, egui::Key::F5),
+    (geng::Key::F6, egui::Key::F6),
+    (geng::Key::F7, egui::Key::F7),
+    (geng::Key::F8, egui::Key::F8),
+    (geng::Key::F9, egui::Key::F9),
+    (geng::Key::F10, egui::Key::F10),
+    (geng::Key::F11, egui::Key::F11),
+    (geng::Key::F12, egui::Key::F12),
+    (geng::Key::F13, egui::Key::F13),
+    (geng::Key::F14, egui::Key::F14),
+    (geng::Key::F15, egui::Key::F15),
+    (geng::Key::F16, egui::Key::F16),
+    (geng::Key::F17, egui::Key::F17),
+    (geng::Key::F18, egui::Key::F18),
+    (geng::Key::F19, egui::Key::F19),
+    (geng::Key::F20, egui::Key::F20),
+];
+
+pub fn egui_key(geng_key: geng::Key) -> Option<egui::Key> {
+    KEYS.iter()
+        .find(|&&(key, _)| key == geng_key)
+        .map(|&(_, key)| key)
+}
+
+pub fn geng_key(egui_key: egui::Key) -> Option<geng::Key> {
+    KEYS.iter()
+        .find(|&&(_, key)| key == egui_key)
+        .map(|&(key, _)| key)
+}
+
+/// Tracks which modifier keys are held down, on both sides of the keyboard.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ModifierKeys {
+    alt_left: bool,
+    alt_right: bool,
+    ctrl_left: bool,
+    ctrl_right: bool,
+    shift_left: bool,
+    shift_right: bool,
+    super_left: bool,
+    super_right: bool,
+}
+
+impl ModifierKeys {
+    /// Update the state from a key press or release. Non-modifier keys are ignored.
+    pub fn update(&mut self, key: geng::Key, pressed: bool) {
+        let flag = match key {
+            geng::Key::AltLeft => &mut self.alt_left,
+            geng::Key::AltRight => &mut self.alt_right,
+            geng::Key::ControlLeft => &mut self.ctrl_left,
+            geng::Key::ControlRight => &mut self.ctrl_right,
+            geng::Key::ShiftLeft => &mut self.shift_left,
+            geng::Key::ShiftRight => &mut self.shift_right,
+            geng::Key::SuperLeft => &mut self.super_left,
+            geng::Key::SuperRight => &mut self.super_right,
+            _ => return,
+        };
+        *flag = pressed;
+    }
+
+    pub fn to_egui(self) -> egui::Modifiers {
+        let ctrl = self.ctrl_left || self.ctrl_right;
+        let logo = self.super_left || self.super_right;
+        // On Mac the Command key is the Super key, elsewhere it is Ctrl.
+        let mac = is_mac();
+        egui::Modifiers {
+            alt: self.alt_left || self.alt_right,
+            ctrl,
+            shift: self.shift_left || self.shift_right,
+            mac_cmd: mac && logo,
+            command: if mac { logo } else { ctrl },
+        }
+    }
+}
+
+/// Whether we are running on a Mac, where the Command key is the Super key.
+/// On the web this depends on the browser's platform rather than on the build target.
+fn is_mac() -> bool {
+    #[cfg(target_arch = "wasm32")]
+    {
+        static IS_MAC: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+        *IS_MAC.get_or_init(|| {
+            web_sys::window()
+                .and_then(|window| window.navigator().user_agent().ok())
+                .is_some_and(|agent| agent.contains("Mac"))
+        })
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        cfg!(any(target_os = "macos", target_os = "ios"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_round_trip() {
+        for &(geng_key_, egui_key_) in KEYS {
+            assert_eq!(egui_key(geng_key_), Some(egui_key_), "{geng_key_:?}");
+            let back = geng_key(egui_key_).expect("mapped egui key has no geng key");
+            assert_eq!(egui_key(back), Some(egui_key_), "{egui_key_:?}");
+        }
+    }
+
+    #[test]
+    fn every_egui_key_is_mapped() {
+        for &key in egui::Key::ALL {
+            let skip = match key {
+                // Produced from shortcuts instead of physical keys
+                egui::Key::Copy | egui::Key::Cut | egui::Key::Paste => true,
+                // Only typed with Shift, there is no such physical key
+                egui::Key::Colon | egui::Key::Pipe | egui::Key::Questionmark => true,
+                // geng has no keys past F20
+                _ => key
+                    .name()
+                    .strip_prefix('F')
+                    .and_then(|n| n.parse::<u32>().ok())
+                    .is_some_and(|n| n > 20),
+            };
+            if !skip {
+                assert!(geng_key(key).is_some(), "{key:?} is not mapped");
+            }
+        }
+    }
+}
@@ -1,8 +1,39 @@
+mod animation;
+mod atlas;
+mod clipboard;
+mod clock;
+mod fonts;
 mod icon;
+mod keys;
+mod nine_slice;
 mod painter;
+mod panel;
+mod state;
+mod user_textures;
+mod viewport;
 
-use self::painter::Painter;
-pub use self::{icon::Icon, painter::CallbackFn};
+pub use self::{
+    animation::{AnimatedIcon, AnimationFrame},
+    atlas::{Atlas, AtlasFrame, AtlasFrames, AtlasIcon},
+    clipboard::{Clipboard, MemoryClipboard},
+    clock::{Clock, ManualClock, SystemClock},
+    fonts::{EguiFont, Fonts},
+    icon::{Icon, IconImage, SharedIcon},
+    nine_slice::{ButtonSkin, NineSlice, SliceFill},
+    painter::{CallbackCamera, CallbackContext, CallbackFn},
+    panel::{panel_uv, EguiPanel},
+    state::{EguiState, WithEgui},
+    user_textures::UserTextures,
+    viewport::Viewport,
+};
+use self::{
+    icon::straight_alpha,
+    keys::{egui_key, geng_key, ModifierKeys},
+    painter::Painter,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::clipboard::SystemClipboard;
 
 use geng::prelude::*;
 
@@ -14,23 +45,58 @@ pub struct EguiGeng {
     egui_ctx: egui::Context,
     egui_input: egui::RawInput,
     painter: Painter,
+    user_textures: UserTextures,
     shapes: Option<Vec<egui::epaint::ClippedShape>>,
     textures_delta: egui::TexturesDelta,
+    /// Height of the screen in pixels.
     screen_height: f32,
+    /// Scale factor of the display, independent of the egui zoom.
+    /// Set by the user since geng does not report it.
+    native_pixels_per_point: f32,
+    /// Pixels per point used by the last frame, including the egui zoom.
+    pixels_per_point: f32,
     pointer_position: vec2<f64>,
+    modifier_keys: ModifierKeys,
+    clipboard: Box<dyn Clipboard>,
+    clock: Box<dyn Clock>,
+    /// Time of the last frame according to the clock.
+    last_frame_time: Option<f64>,
+    /// Whether to leave the cursor to the game when the pointer is not over egui.
+    cursor_passthrough: bool,
+    /// The cursor icon last applied to the window, if egui is in control of it.
+    cursor_icon: Option<egui::CursorIcon>,
+    /// Whether we have asked the window to start a text edit session.
+    text_editing: bool,
+    /// Contents of the window text edit session as of the last [geng::Event::EditText].
+    edit_text: String,
+    /// Where egui wants the IME candidate box to appear, in window coordinates.
+    ime_rect: Option<Aabb2<f32>>,
 }
 
 impl EguiGeng {
     pub fn new(geng: &Geng) -> Self {
+        let user_textures = UserTextures::default();
         Self {
             geng: geng.clone(),
             egui_ctx: egui::Context::default(),
             egui_input: egui::RawInput::default(),
-            painter: Painter::new(geng),
+            painter: Painter::new(geng, user_textures.clone()),
+            user_textures,
             shapes: None,
             textures_delta: egui::TexturesDelta::default(),
             screen_height: 1.0,
+            native_pixels_per_point: 1.0,
+            pixels_per_point: 1.0,
             pointer_position: vec2::ZERO,
+            modifier_keys: ModifierKeys::default(),
+            clipboard: clipboard::default_clipboard(),
+            clock: Box::new(SystemClock::new()),
+            last_frame_time: None,
+            cursor_passthrough: false,
+            cursor_icon: None,
+            text_editing: false,
+            edit_text: String::new(),
+            ime_rect: None,
         }
     }
 
@@ -39,6 +105,65 @@ impl EguiGeng {
         &self.egui_ctx
     }
 
+    /// Registry of game textures that can be displayed in egui.
+    pub fn user_textures(&self) -> &UserTextures {
+        &self.user_textures
+    }
+
+    /// Replace the clipboard used for copy and paste.
+    /// By default the system clipboard is used where available.
+    /// On the web there is no synchronous access to the browser clipboard,
+    /// so a [MemoryClipboard] is used and text can only be copied and pasted within the app.
+    pub fn set_clipboard(&mut self, clipboard: impl Clipboard + 'static) {
+        self.clipboard = Box::new(clipboard);
+    }
+
+    /// Set the scale factor of the display, i.e. how many physical pixels make up one ui point
+    /// before the egui zoom ([egui::Context::set_zoom_factor]) is applied.
+    /// Use it to make the ui readable on HiDPI displays. Defaults to `1.0`.
+    ///
+    /// geng does not expose the display scale factor of its window, so it cannot be
+    /// derived automatically: HiDPI support needs this to be set by the application,
+    /// e.g. from a settings menu or a platform-specific query.
+    pub fn set_native_pixels_per_point(&mut self, native_pixels_per_point: f32) {
+        self.native_pixels_per_point = native_pixels_per_point;
+    }
+
+    /// How many physical pixels make up one ui point, including the egui zoom.
+    pub fn pixels_per_point(&self) -> f32 {
+        self.pixels_per_point
+    }
+
+    /// Replace the clock used to measure frame time.
+    /// By default real time is used.
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.clock = Box::new(clock);
+        self.last_frame_time = None;
+    }
+
+    /// Set whether the framebuffers passed to [Self::draw] expect colors in linear space
+    /// (e.g. sRGB textures or an HDR pipeline) rather than sRGB gamma space.
+    /// Defaults to `false`, which matches the default geng framebuffer.
+    pub fn set_linear_framebuffer(&mut self, linear: bool) {
+        self.painter.linear_output = linear;
+    }
+
+    /// When enabled, the window cursor is only changed while the pointer is over egui,
+    /// so the game is free to set its own cursor otherwise.
+    /// Disabled by default.
+    pub fn set_cursor_passthrough(&mut self, enabled: bool) {
+        self.cursor_passthrough = enabled;
+    }
+
+    /// The area around the text cursor of the focused text field, if any.
+    /// Coordinates are in window pixels with the origin at the bottom-left corner.
+    ///
+    /// geng has no API to position the IME candidate box, so it is not applied to the window.
+    /// Use it to place the candidate box or an on-screen keyboard by platform-specific means.
+    pub fn ime_rect(&self) -> Option<Aabb2<f32>> {
+        self.ime_rect
+    }
+
     /// Call at the beginning of the frame.
     /// Implement your ui logic inbetween [begin_frame] and [end_frame].
     pub fn begin_frame(&mut self) {
@@ -57,9 +182,66 @@ impl EguiGeng {
         }
 
         self.shapes = Some(output.shapes);
+        self.pixels_per_point = output.pixels_per_point;
         self.textures_delta.append(output.textures_delta);
 
-        // TODO: process platform output
+        let platform_output = output.platform_output;
+        self.update_ime(platform_output.ime);
+        clipboard::store_copied_text(self.clipboard.as_mut(), platform_output.copied_text);
+        self.update_cursor(platform_output.cursor_icon);
+
+        // TODO: process the rest of platform output
+    }
+
+    fn update_cursor(&mut self, icon: egui::CursorIcon) {
+        if self.cursor_passthrough
+            && !self.egui_ctx.is_pointer_over_area()
+            && !self.egui_ctx.is_using_pointer()
+        {
+            // Forget the applied icon so it is set again once the pointer is back over egui
+            self.cursor_icon = None;
+            return;
+        }
+        if self.cursor_icon == Some(icon) {
+            return;
+        }
+        self.geng.window().set_cursor_type(cursor_type(icon));
+        self.cursor_icon = Some(icon);
+    }
+
+    /// Start or stop the window text edit session (and with it the IME)
+    /// depending on whether egui has a focused text field.
+    ///
+    /// Only [egui::ImeEvent::Enabled] and [egui::ImeEvent::Disabled] are sent:
+    /// geng does not report composition in progress, so there is no preedit to show,
+    /// and committed text arrives through [geng::Event::EditText].
+    fn update_ime(&mut self, ime: Option<egui::output::IMEOutput>) {
+        self.ime_rect = ime.map(|ime| {
+            let rect = ime.cursor_rect * self.pixels_per_point;
+            Aabb2::from_corners(
+                pos_to_vec(rect.left_top(), self.screen_height),
+                pos_to_vec(rect.right_bottom(), self.screen_height),
+            )
+        });
+
+        let wants_text = ime.is_some();
+        if wants_text == self.text_editing {
+            return;
+        }
+        let window = self.geng.window();
+        if wants_text {
+            self.edit_text.clear();
+            window.start_text_edit("");
+            self.egui_input
+                .events
+                .push(egui::Event::Ime(egui::ImeEvent::Enabled));
+        } else {
+            window.stop_text_edit();
+            self.egui_input
+                .events
+                .push(egui::Event::Ime(egui::ImeEvent::Disabled));
+        }
+        self.text_editing = wants_text;
     }
 
     /// Call after [end_frame] to draw the ui.
@@ -69,17 +251,17 @@ impl EguiGeng {
         self.screen_height = framebuffer_size.y;
         self.egui_input.screen_rect = Some(egui::Rect::from_min_size(
             egui::Pos2::ZERO,
-            egui::Vec2::new(framebuffer_size.x, framebuffer_size.y),
+            egui::Vec2::new(framebuffer_size.x, framebuffer_size.y) / self.pixels_per_point,
         ));
 
         // Render mesh
         if let Some(shapes) = self.shapes.take() {
-            let paint_jobs = self.egui_ctx.tessellate(shapes, 1.0);
+            let paint_jobs = self.egui_ctx.tessellate(shapes, self.pixels_per_point);
             self.painter.paint_and_update_textures(
                 framebuffer,
                 paint_jobs,
                 &self.textures_delta,
-                &self.egui_ctx,
+                self.pixels_per_point,
             );
             self.textures_delta.clear();
         } else {
@@ -87,26 +269,48 @@ impl EguiGeng {
         }
     }
 
+    /// Whether egui wants to handle the event, based on the state of the last frame.
+    /// Such events should usually not be processed by the game.
+    pub fn wants_event(&self, event: &geng::Event) -> bool {
+        match event {
+            geng::Event::MousePress { .. } | geng::Event::Wheel { .. } => {
+                self.egui_ctx.is_pointer_over_area()
+            }
+            // Releases and moves belong to egui while it is dragging something,
+            // hovering alone should not hide the cursor from the game
+            geng::Event::MouseRelease { .. } | geng::Event::CursorMove { .. } => {
+                self.egui_ctx.is_using_pointer()
+            }
+            geng::Event::KeyPress { .. }
+            | geng::Event::KeyRelease { .. }
+            | geng::Event::EditText(_) => self.egui_ctx.wants_keyboard_input(),
+            _ => false,
+        }
+    }
+
     /// Call every time you receive an event from the engine in [geng::State::handle_event].
-    pub fn handle_event(&mut self, event: geng::Event) {
+    ///
+    /// Returns whether egui wants the event (see [Self::wants_event]),
+    /// in which case the game should usually ignore it.
+    pub fn handle_event(&mut self, event: geng::Event) -> bool {
+        let wants_event = self.wants_event(&event);
         match event {
             geng::Event::Wheel { delta } => {
-                let window = self.geng.window();
                 self.egui_input.events.push(egui::Event::MouseWheel {
                     unit: egui::MouseWheelUnit::Point,
                     delta: egui::Vec2::new(0.0, delta as f32),
-                    modifiers: egui::Modifiers {
-                        alt: window.is_key_pressed(geng::Key::AltLeft),
-                        ctrl: window.is_key_pressed(geng::Key::ControlLeft),
-                        shift: window.is_key_pressed(geng::Key::ShiftLeft),
-                        mac_cmd: false,
-                        command: false,
-                    },
+                    modifiers: self.get_modifiers(),
                 });
             }
             geng::Event::KeyPress { key } => {
+                self.modifier_keys.update(key, true);
                 if let Some(key) = egui_key(key) {
                     let modifiers = self.get_modifiers();
+                    if let Some(event) =
+                        clipboard::shortcut_event(key, modifiers, self.clipboard.as_mut())
+                    {
+                        self.egui_input.events.push(event);
+                    }
                     self.egui_input.events.push(egui::Event::Key {
                         key,
                         physical_key: None,
@@ -114,17 +318,10 @@ impl EguiGeng {
                         pressed: true,
                         repeat: false,
                     });
-                    if let Some(mut symbol) = key_char(key) {
-                        if modifiers.shift {
-                            symbol = symbol.to_uppercase().next().unwrap();
-                        }
-                        self.egui_input
-                            .events
-                            .push(egui::Event::Text(symbol.to_string()));
-                    }
                 }
             }
             geng::Event::KeyRelease { key } => {
+                self.modifier_keys.update(key, false);
                 if let Some(key) = egui_key(key) {
                     self.egui_input.events.push(egui::Event::Key {
                         key,
@@ -135,6 +332,20 @@ impl EguiGeng {
                     });
                 }
             }
+            geng::Event::EditText(text) => {
+                // The window reports the whole edited text, so only forward what was inserted,
+                // wherever the caret is. Deletions arrive as regular key presses.
+                // geng has no composition events: text composed with an IME is only reported
+                // once it is committed, so it is forwarded the same way instead of as
+                // egui::ImeEvent::Preedit/Commit.
+                let typed = inserted_text(&self.edit_text, &text);
+                // Control characters (e.g. tabs or `\u{3}` from Ctrl+C) arrive as key presses
+                let typed: String = typed.chars().filter(|&c| is_printable_char(c)).collect();
+                if !typed.is_empty() {
+                    self.egui_input.events.push(egui::Event::Text(typed));
+                }
+                self.edit_text = text;
+            }
             geng::Event::MousePress { button } => {
                 let button = egui_button(button);
                 self.egui_input.events.push(egui::Event::PointerButton {
@@ -161,25 +372,54 @@ impl EguiGeng {
             }
             _ => (),
         }
+        wants_event
+    }
+
+    /// Prepare for not receiving events or being drawn for a while,
+    /// e.g. when another [geng::State] takes over.
+    fn suspend(&mut self) {
+        // The frame will not be drawn
+        self.shapes = None;
+
+        // Keys and buttons may be released while another state is active
+        self.modifier_keys = ModifierKeys::default();
+        self.pointer_gone();
+        self.egui_input
+            .events
+            .push(egui::Event::WindowFocused(false));
+
+        // Let the other state control the window
+        self.update_ime(None);
+        self.cursor_icon = None;
+    }
+
+    /// Tell egui that the pointer has left the ui.
+    fn pointer_gone(&mut self) {
+        self.egui_input.events.push(egui::Event::PointerGone);
     }
 
     fn gather_input(&mut self) {
+        clock::update_input_time(
+            self.clock.as_mut(),
+            &mut self.last_frame_time,
+            &mut self.egui_input,
+        );
+
         self.egui_input.modifiers = self.get_modifiers();
+        self.egui_input
+            .viewports
+            .entry(egui::ViewportId::ROOT)
+            .or_default()
+            .native_pixels_per_point = Some(self.native_pixels_per_point);
     }
 
     fn get_modifiers(&self) -> egui::Modifiers {
-        let window = self.geng.window();
-        // TODO: check
-        egui::Modifiers {
-            alt: window.is_key_pressed(geng::Key::AltLeft),
-            ctrl: window.is_key_pressed(geng::Key::ControlLeft),
-            shift: window.is_key_pressed(geng::Key::ShiftLeft),
-            ..default()
-        }
+        self.modifier_keys.to_egui()
     }
 
+    /// Converts a window position (in pixels) to an egui position (in points).
     fn mouse_to_pos(&self, mouse: vec2<f64>) -> egui::Pos2 {
-        egui::Pos2::new(mouse.x as f32, self.screen_height - mouse.y as f32)
+        egui::Pos2::new(mouse.x as f32, self.screen_height - mouse.y as f32) / self.pixels_per_point
     }
 }
 
@@ -188,6 +428,46 @@ fn pos_to_vec(pos: egui::Pos2, height: f32) -> vec2<f32> {
     vec2(pos.x, height - pos.y)
 }
 
+/// The text inserted at a single place to turn `old` into `new`,
+/// i.e. what is left of `new` after removing the parts it has in common with `old` at both ends.
+fn inserted_text<'a>(old: &str, new: &'a str) -> &'a str {
+    let prefix = old
+        .char_indices()
+        .zip(new.chars())
+        .find(|&((_, a), b)| a != b)
+        .map_or(old.len().min(new.len()), |((i, _), _)| i);
+    let (old, new) = (&old[prefix..], &new[prefix..]);
+    let suffix: usize = old
+        .chars()
+        .rev()
+        .zip(new.chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum();
+    &new[..new.len() - suffix]
+}
+
+/// Whether the character should be inserted as text, same as in egui-winit.
+fn is_printable_char(chr: char) -> bool {
+    let is_in_private_use_area = ('\u{e000}'..='\u{f8ff}').contains(&chr)
+        || ('\u{f0000}'..='\u{ffffd}').contains(&chr)
+        || ('\u{100000}'..='\u{10fffd}').contains(&chr);
+    !is_in_private_use_area && !chr.is_control()
+}
+
+/// Converts [egui::CursorIcon] to the closest [geng::CursorType].
+fn cursor_type(icon: egui::CursorIcon) -> geng::CursorType {
+    use egui::CursorIcon as Cursor;
+    match icon {
+        Cursor::None => geng::CursorType::None,
+        Cursor::PointingHand => geng::CursorType::Pointer,
+        Cursor::Grab | Cursor::Grabbing | Cursor::Move | Cursor::AllScroll => {
+            geng::CursorType::Drag
+        }
+        _ => geng::CursorType::Default,
+    }
+}
+
 fn egui_button(geng_button: geng::MouseButton) -> egui::PointerButton {
     match geng_button {
         geng::MouseButton::Left => egui::PointerButton::Primary,
@@ -196,97 +476,28 @@ fn egui_button(geng_button: geng::MouseButton) -> egui::PointerButton {
     }
 }
 
-fn egui_key(geng_key: geng::Key) -> Option<egui::Key> {
-    use egui::Key::*;
-    match geng_key {
-        geng::Key::Digit0 | geng::Key::Numpad0 => Some(Num0),
-        geng::Key::Digit1 | geng::Key::Numpad1 => Some(Num1),
-        geng::Key::Digit2 | geng::Key::Numpad2 => Some(Num2),
-        geng::Key::Digit3 | geng::Key::Numpad3 => Some(Num3),
-        geng::Key::Digit4 | geng::Key::Numpad4 => Some(Num4),
-        geng::Key::Digit5 | geng::Key::Numpad5 => Some(Num5),
-        geng::Key::Digit6 | geng::Key::Numpad6 => Some(Num6),
-        geng::Key::Digit7 | geng::Key::Numpad7 => Some(Num7),
-        geng::Key::Digit8 | geng::Key::Numpad8 => Some(Num8),
-        geng::Key::Digit9 | geng::Key::Numpad9 => Some(Num9),
-        geng::Key::A => Some(A),
-        geng::Key::B => Some(B),
-        geng::Key::C => Some(C),
-        geng::Key::D => Some(D),
-        geng::Key::E => Some(E),
-        geng::Key::F => Some(F),
-        geng::Key::G => Some(G),
-        geng::Key::H => Some(H),
-        geng::Key::I => Some(I),
-        geng::Key::J => Some(J),
-        geng::Key::K => Some(K),
-        geng::Key::L => Some(L),
-        geng::Key::M => Some(M),
-        geng::Key::N => Some(N),
-        geng::Key::O => Some(O),
-        geng::Key::P => Some(P),
-        geng::Key::Q => Some(Q),
-        geng::Key::R => Some(R),
-        geng::Key::S => Some(S),
-        geng::Key::T => Some(T),
-        geng::Key::U => Some(U),
-        geng::Key::V => Some(V),
-        geng::Key::W => Some(W),
-        geng::Key::X => Some(X),
-        geng::Key::Y => Some(Y),
-        geng::Key::Z => Some(Z),
-        geng::Key::Escape => Some(Escape),
-        geng::Key::Space => Some(Space),
-        geng::Key::Enter => Some(Enter),
-        geng::Key::Backspace => Some(Backspace),
-        geng::Key::ArrowLeft => Some(ArrowLeft),
-        geng::Key::ArrowRight => Some(ArrowRight),
-        geng::Key::ArrowUp => Some(ArrowUp),
-        geng::Key::ArrowDown => Some(ArrowDown),
-        geng::Key::PageUp => Some(PageUp),
-        geng::Key::PageDown => Some(PageDown),
-        _ => None,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inserted_text_append() {
+        assert_eq!(inserted_text("ab", "abc"), "c");
+        assert_eq!(inserted_text("", "abc"), "abc");
+        assert_eq!(inserted_text("aa", "aaa"), "a");
+    }
+
+    #[test]
+    fn inserted_text_middle() {
+        assert_eq!(inserted_text("abc", "abxc"), "x");
+        assert_eq!(inserted_text("abc", "xyabc"), "xy");
+        assert_eq!(inserted_text("añc", "añéßc"), "éß");
     }
-}
 
-fn key_char(key: egui::Key) -> Option<char> {
-    match key {
-        egui::Key::A => Some('a'),
-        egui::Key::B => Some('b'),
-        egui::Key::C => Some('c'),
-        egui::Key::D => Some('d'),
-        egui::Key::E => Some('e'),
-        egui::Key::F => Some('f'),
-        egui::Key::G => Some('g'),
-        egui::Key::H => Some('h'),
-        egui::Key::I => Some('i'),
-        egui::Key::J => Some('j'),
-        egui::Key::K => Some('k'),
-        egui::Key::L => Some('l'),
-        egui::Key::M => Some('m'),
-        egui::Key::N => Some('n'),
-        egui::Key::O => Some('o'),
-        egui::Key::P => Some('p'),
-        egui::Key::Q => Some('q'),
-        egui::Key::R => Some('r'),
-        egui::Key::S => Some('s'),
-        egui::Key::T => Some('t'),
-        egui::Key::U => Some('u'),
-        egui::Key::V => Some('v'),
-        egui::Key::W => Some('w'),
-        egui::Key::X => Some('x'),
-        egui::Key::Y => Some('y'),
-        egui::Key::Z => Some('z'),
-        egui::Key::Num0 => Some('0'),
-        egui::Key::Num1 => Some('1'),
-        egui::Key::Num2 => Some('2'),
-        egui::Key::Num3 => Some('3'),
-        egui::Key::Num4 => Some('4'),
-        egui::Key::Num5 => Some('5'),
-        egui::Key::Num6 => Some('6'),
-        egui::Key::Num7 => Some('7'),
-        egui::Key::Num8 => Some('8'),
-        egui::Key::Num9 => Some('9'),
-        _ => None,
+    #[test]
+    fn inserted_text_deletion() {
+        assert_eq!(inserted_text("abc", "ac"), "");
+        assert_eq!(inserted_text("abc", "ab"), "");
+        assert_eq!(inserted_text("abc", ""), "");
     }
 }
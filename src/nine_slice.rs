@@ -0,0 +1,279 @@
+use super::*;
+
+/// How the edges and the center of a [NineSlice] fill their space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SliceFill {
+    Stretch,
+    /// Repeat the slice at its size, cutting off the last copy.
+    Tile,
+}
+
+/// A texture split into a 3x3 grid by border insets, for game-styled frames.
+/// The corners keep their size while the edges and the center fill the space between them.
+///
+/// egui frames can only draw flat colors, so use [Self::show] instead of [egui::Frame::show].
+/// For windows and panels, pass [Self::frame] to their `frame` method
+/// and wrap their contents in [Self::background].
+#[derive(Clone)]
+pub struct NineSlice {
+    pub icon: AtlasIcon,
+    /// Size of the borders in texture pixels.
+    pub insets: egui::Margin,
+    /// Size of a texture pixel in ui points.
+    pub scale: f32,
+    pub edges: SliceFill,
+    pub center: SliceFill,
+    pub tint: egui::Color32,
+}
+
+impl NineSlice {
+    /// Stretches the edges and the center, with texture pixels the size of ui points.
+    pub fn new(icon: AtlasIcon, insets: impl Into<egui::Margin>) -> Self {
+        Self {
+            icon,
+            insets: insets.into(),
+            scale: 1.0,
+            edges: SliceFill::Stretch,
+            center: SliceFill::Stretch,
+            tint: egui::Color32::WHITE,
+        }
+    }
+
+    pub fn with_scale(self, scale: f32) -> Self {
+        Self { scale, ..self }
+    }
+
+    pub fn with_fill(self, edges: SliceFill, center: SliceFill) -> Self {
+        Self {
+            edges,
+            center,
+            ..self
+        }
+    }
+
+    pub fn with_tint(self, tint: egui::Color32) -> Self {
+        Self { tint, ..self }
+    }
+
+    /// Size of the borders in ui points.
+    pub fn margin(&self) -> egui::Margin {
+        self.insets * self.scale
+    }
+
+    /// A frame without a background that leaves room for the borders.
+    pub fn frame(&self) -> egui::Frame {
+        egui::Frame::none().inner_margin(self.margin())
+    }
+
+    /// Show the contents framed by the nine-slice, like [egui::Frame::show].
+    pub fn show<R>(
+        &self,
+        ui: &mut egui::Ui,
+        add_contents: impl FnOnce(&mut egui::Ui) -> R,
+    ) -> egui::InnerResponse<R> {
+        let background = ui.painter().add(egui::Shape::Noop);
+        let inner = self.frame().show(ui, add_contents);
+        ui.painter().set(background, self.mesh(inner.response.rect));
+        inner
+    }
+
+    /// Paint the nine-slice behind the contents of a ui that already leaves room for the borders,
+    /// e.g. a [egui::Window] or a panel using [Self::frame].
+    ///
+    /// The nine-slice covers the space used by the contents,
+    /// call `ui.expand_to_include_rect(ui.max_rect())` inside to fill the whole panel.
+    pub fn background<R>(
+        &self,
+        ui: &mut egui::Ui,
+        add_contents: impl FnOnce(&mut egui::Ui) -> R,
+    ) -> R {
+        let background = ui.painter().add(egui::Shape::Noop);
+        let inner = add_contents(ui);
+        let rect = self.margin().expand_rect(ui.min_rect());
+        // The borders lie outside of the contents, so they must not be clipped with them
+        let mut painter = ui.painter().clone();
+        painter.set_clip_rect(ui.clip_rect().union(rect));
+        painter.set(background, self.mesh(rect));
+        inner
+    }
+
+    /// Paint the nine-slice over the rect.
+    pub fn paint(&self, painter: &egui::Painter, rect: egui::Rect) {
+        painter.add(self.mesh(rect));
+    }
+
+    /// A single textured mesh covering the rect.
+    pub fn mesh(&self, rect: egui::Rect) -> egui::Mesh {
+        let mut mesh = egui::Mesh::with_texture(self.icon.icon.id());
+        let size = self.icon.size.map(|x| x as f32);
+        let insets = self.insets;
+        let margin = self.margin();
+
+        // Shrink the borders when the rect is too small to fit them
+        let fit = |start: f32, end: f32, available: f32| {
+            let scale = if start + end > available {
+                available.max(0.0) / (start + end)
+            } else {
+                1.0
+            };
+            (start * scale, end * scale)
+        };
+        let (left, right) = fit(margin.left, margin.right, rect.width());
+        let (top, bottom) = fit(margin.top, margin.bottom, rect.height());
+
+        // Ranges of the columns and rows on the screen and in the texture (in pixels)
+        let columns = [
+            (
+                egui::Rangef::new(rect.left(), rect.left() + left),
+                egui::Rangef::new(0.0, insets.left),
+            ),
+            (
+                egui::Rangef::new(rect.left() + left, rect.right() - right),
+                egui::Rangef::new(insets.left, size.x - insets.right),
+            ),
+            (
+                egui::Rangef::new(rect.right() - right, rect.right()),
+                egui::Rangef::new(size.x - insets.right, size.x),
+            ),
+        ];
+        let rows = [
+            (
+                egui::Rangef::new(rect.top(), rect.top() + top),
+                egui::Rangef::new(0.0, insets.top),
+            ),
+            (
+                egui::Rangef::new(rect.top() + top, rect.bottom() - bottom),
+                egui::Rangef::new(insets.top, size.y - insets.bottom),
+            ),
+            (
+                egui::Rangef::new(rect.bottom() - bottom, rect.bottom()),
+                egui::Rangef::new(size.y - insets.bottom, size.y),
+            ),
+        ];
+
+        let uv = self.icon.uv;
+        let to_uv = |x: f32, y: f32| {
+            egui::pos2(
+                uv.min.x + x / size.x * uv.width(),
+                uv.min.y + y / size.y * uv.height(),
+            )
+        };
+
+        for (row, &(screen_y, texture_y)) in rows.iter().enumerate() {
+            for (column, &(screen_x, texture_x)) in columns.iter().enumerate() {
+                let fill = match (column == 1, row == 1) {
+                    (true, true) => self.center,
+                    (false, false) => SliceFill::Stretch,
+                    _ => self.edges,
+                };
+                let tile = fill == SliceFill::Tile;
+                let xs = self.segments(screen_x, texture_x, tile && column == 1);
+                let ys = self.segments(screen_y, texture_y, tile && row == 1);
+                for &(screen_y, texture_y) in &ys {
+                    for &(screen_x, texture_x) in &xs {
+                        mesh.add_rect_with_uv(
+                            egui::Rect::from_x_y_ranges(screen_x, screen_y),
+                            egui::Rect::from_min_max(
+                                to_uv(texture_x.min, texture_y.min),
+                                to_uv(texture_x.max, texture_y.max),
+                            ),
+                            self.tint,
+                        );
+                    }
+                }
+            }
+        }
+        mesh
+    }
+
+    /// Splits a range on the screen into the parts of the texture range drawn there.
+    fn segments(
+        &self,
+        screen: egui::Rangef,
+        texture: egui::Rangef,
+        tile: bool,
+    ) -> Vec<(egui::Rangef, egui::Rangef)> {
+        if screen.span() <= 0.0 || texture.span() <= 0.0 {
+            return Vec::new();
+        }
+        let tile_size = texture.span() * self.scale;
+        if !tile || tile_size <= 0.0 {
+            return vec![(screen, texture)];
+        }
+        let mut segments = Vec::new();
+        let mut start = screen.min;
+        while start < screen.max {
+            let end = (start + tile_size).min(screen.max);
+            let part = (end - start) / tile_size;
+            segments.push((
+                egui::Rangef::new(start, end),
+                egui::Rangef::new(texture.min, texture.min + texture.span() * part),
+            ));
+            start = end;
+        }
+        segments
+    }
+}
+
+/// Nine-slice skins for the states of a button.
+#[derive(Clone)]
+pub struct ButtonSkin {
+    pub normal: NineSlice,
+    /// Used while the pointer is over the button, if set.
+    pub hovered: Option<NineSlice>,
+    /// Used while the button is held down, if set.
+    pub pressed: Option<NineSlice>,
+}
+
+impl ButtonSkin {
+    pub fn new(normal: NineSlice) -> Self {
+        Self {
+            normal,
+            hovered: None,
+            pressed: None,
+        }
+    }
+
+    pub fn with_hovered(self, hovered: NineSlice) -> Self {
+        Self {
+            hovered: Some(hovered),
+            ..self
+        }
+    }
+
+    pub fn with_pressed(self, pressed: NineSlice) -> Self {
+        Self {
+            pressed: Some(pressed),
+            ..self
+        }
+    }
+
+    /// Show a button with the skin instead of the egui style background.
+    pub fn button(&self, ui: &mut egui::Ui, text: impl Into<egui::WidgetText>) -> egui::Response {
+        let background = ui.painter().add(egui::Shape::Noop);
+        let margin = self.normal.margin();
+        let response = ui
+            .scope(|ui| {
+                ui.spacing_mut().button_padding =
+                    egui::vec2(margin.left.max(margin.right), margin.top.max(margin.bottom));
+                // Without a frame egui drops the padding, so keep it but make it invisible
+                ui.add(
+                    egui::Button::new(text)
+                        .fill(egui::Color32::TRANSPARENT)
+                        .stroke(egui::Stroke::NONE),
+                )
+            })
+            .inner;
+
+        let pressed = response
+            .is_pointer_button_down_on()
+            .then_some(&self.pressed);
+        let hovered = response.hovered().then_some(&self.hovered);
+        let skin = pressed
+            .and_then(Option::as_ref)
+            .or(hovered.and_then(Option::as_ref))
+            .unwrap_or(&self.normal);
+        ui.painter().set(background, skin.mesh(response.rect));
+        response
+    }
+}
@@ -1,19 +1,54 @@
 use super::*;
 
+/// What a [CallbackFn] gets to render with.
+pub struct CallbackContext<'a> {
+    pub geng: &'a Geng,
+    /// Where the callback is placed in the ui, in points.
+    pub info: egui::PaintCallbackInfo,
+    /// A camera to draw in the pixels of the callback rect.
+    pub camera: CallbackCamera,
+}
+
+/// A camera mapping the pixels of a callback rect (with the origin at its bottom-left corner)
+/// onto the framebuffer of a [CallbackFn], which only covers the visible part of the rect.
+#[derive(Debug, Clone, Copy)]
+pub struct CallbackCamera {
+    /// Size of the whole callback rect in pixels.
+    pub size: vec2<f32>,
+    /// The part of the callback rect covered by the framebuffer, in the same pixels.
+    pub visible: Aabb2<f32>,
+}
+
+impl geng::AbstractCamera2d for CallbackCamera {
+    fn view_matrix(&self) -> mat3<f32> {
+        mat3::identity()
+    }
+
+    fn projection_matrix(&self, _framebuffer_size: vec2<f32>) -> mat3<f32> {
+        let visible_size = self.visible.max - self.visible.min;
+        mat3::translate(vec2(-1.0, -1.0))
+            * mat3::scale(visible_size.map(|x| 2.0 / x))
+            * mat3::translate(-self.visible.min)
+    }
+}
+
 /// A callback function that can be used to compose an [`egui::PaintCallback`] for custom rendering.
 ///
-/// The callback is passed, the [`egui::PaintCallbackInfo`] and the [`GraphicsContext`] which can be used to
-/// access the OpenGL context.
+/// The callback is passed a [`CallbackContext`] and a framebuffer covering the part of the callback rect
+/// that is visible (i.e. not clipped or off-screen), so a callback inside a large scroll area
+/// doesn't need a huge render target. Draw with [`CallbackContext::camera`] to use the pixels
+/// of the whole callback rect regardless of what is visible. The framebuffer has a depth buffer
+/// and both are cleared before every call. Its colors are then drawn into the ui as premultiplied.
 pub struct CallbackFn {
     #[allow(clippy::type_complexity)]
-    f: Box<dyn Fn(egui::PaintCallbackInfo, &mut ugli::Framebuffer) + Sync + Send>,
+    f: std::sync::Mutex<Box<dyn FnMut(&CallbackContext, &mut ugli::Framebuffer) + Send>>,
 }
 
 impl CallbackFn {
     pub fn new(
-        callback: impl Fn(egui::PaintCallbackInfo, &mut ugli::Framebuffer) + Sync + Send + 'static,
+        callback: impl FnMut(&CallbackContext, &mut ugli::Framebuffer) + Send + 'static,
     ) -> Self {
-        let f = Box::new(callback);
+        let f = std::sync::Mutex::new(Box::new(callback) as Box<_>);
         CallbackFn { f }
     }
 }
@@ -23,10 +58,55 @@ pub struct Painter {
     textured_program: ugli::Program,
     // egui_texture_version: u64,
     textures: HashMap<egui::TextureId, ugli::Texture>,
+    user_textures: UserTextures,
+    /// Vertices of the current frame, reused across frames to avoid reallocating.
+    /// ugli has no index buffers, so triangles are stored unrolled.
+    vertices: ugli::VertexBuffer<draw2d::TexturedVertex>,
+    /// Render targets for paint callbacks, reused across frames.
+    callback_targets: Vec<CallbackTarget>,
+    /// Whether to convert colors to linear space before writing them to the framebuffer.
+    pub linear_output: bool,
+}
+
+/// Where a paint callback renders to.
+struct CallbackTarget {
+    texture: ugli::Texture,
+    depth_buffer: ugli::Renderbuffer<ugli::DepthComponent>,
+}
+
+impl CallbackTarget {
+    fn new(ugli: &Ugli, size: vec2<usize>) -> Self {
+        Self {
+            texture: ugli::Texture::new_uninitialized(ugli, size),
+            depth_buffer: ugli::Renderbuffer::new(ugli, size),
+        }
+    }
+}
+
+/// Consecutive meshes sharing a texture and a clip rect, drawn in a single call.
+struct Batch {
+    texture_id: egui::TextureId,
+    clip_rect: egui::Rect,
+    /// Clip rect in framebuffer pixels.
+    clip_aabb: Aabb2<usize>,
+    /// Range in [Painter::vertices].
+    vertices: std::ops::Range<usize>,
+}
+
+enum PaintJob {
+    Batch(Batch),
+    Callback {
+        callback: egui::PaintCallback,
+        clip_rect: egui::Rect,
+        clip_aabb: Aabb2<usize>,
+        camera: CallbackCamera,
+        /// Range in [Painter::vertices] covering the visible part of the callback rect.
+        vertices: std::ops::Range<usize>,
+    },
 }
 
 impl Painter {
-    pub fn new(geng: &Geng) -> Self {
+    pub fn new(geng: &Geng, user_textures: UserTextures) -> Self {
         Self {
             geng: geng.clone(),
             textured_program: geng
@@ -35,6 +115,10 @@ impl Painter {
                 .unwrap(),
             // egui_texture_version: 0,
             textures: HashMap::new(),
+            user_textures,
+            vertices: ugli::VertexBuffer::new_dynamic(geng.ugli(), Vec::new()),
+            callback_targets: Vec::new(),
+            linear_output: false,
         }
     }
 
@@ -43,13 +127,13 @@ impl Painter {
         framebuffer: &mut ugli::Framebuffer,
         primtives: Vec<egui::ClippedPrimitive>,
         textures_delta: &egui::TexturesDelta,
-        context: &egui::Context,
+        pixels_per_point: f32,
     ) {
         for (id, image_delta) in &textures_delta.set {
             self.set_texture(*id, image_delta);
         }
 
-        self.paint(framebuffer, primtives, context);
+        self.paint(framebuffer, primtives, pixels_per_point);
 
         for &id in &textures_delta.free {
             self.free_texture(id);
@@ -60,98 +144,236 @@ impl Painter {
         &mut self,
         framebuffer: &mut ugli::Framebuffer,
         primitives: Vec<egui::ClippedPrimitive>,
-        context: &egui::Context,
+        pixels_per_point: f32,
     ) {
-        let screen_size_in_pixels = framebuffer.size().map(|x| x as f32);
-        // let screen_size_in_points = (
-        //     screen_size_in_pixels.x / context.pixels_per_point(),
-        //     screen_size_in_pixels.y / context.pixels_per_point(),
-        // );
+        let framebuffer_size = framebuffer.size();
+        let screen_size_in_pixels = framebuffer_size.map(|x| x as f32);
 
-        // Render mesh
+        // Collect the vertices of all meshes into the shared buffer,
+        // merging consecutive meshes that can be drawn in a single call
+        self.vertices.clear();
+        let mut jobs: Vec<PaintJob> = Vec::new();
         for clipped in primitives {
             match clipped.primitive {
                 egui::epaint::Primitive::Mesh(mesh) => {
-                    self.paint_job(framebuffer, clipped.clip_rect, mesh)
+                    let Some(clip_aabb) =
+                        clip_aabb(clipped.clip_rect, pixels_per_point, framebuffer_size)
+                    else {
+                        // Completely clipped
+                        continue;
+                    };
+
+                    // Convert egui vertices to geng vertices
+                    let start = self.vertices.len();
+                    self.vertices.extend(mesh.indices.iter().map(|&i| {
+                        textured_vertex(
+                            mesh.vertices[i as usize],
+                            screen_size_in_pixels.y,
+                            pixels_per_point,
+                        )
+                    }));
+                    let end = self.vertices.len();
+
+                    if let Some(PaintJob::Batch(batch)) = jobs.last_mut() {
+                        if batch.texture_id == mesh.texture_id
+                            && batch.clip_rect == clipped.clip_rect
+                        {
+                            batch.vertices.end = end;
+                            continue;
+                        }
+                    }
+                    jobs.push(PaintJob::Batch(Batch {
+                        texture_id: mesh.texture_id,
+                        clip_rect: clipped.clip_rect,
+                        clip_aabb,
+                        vertices: start..end,
+                    }));
                 }
                 egui::epaint::Primitive::Callback(callback) => {
-                    let info = egui::PaintCallbackInfo {
-                        viewport: callback.rect,
-                        clip_rect: clipped.clip_rect,
-                        pixels_per_point: context.pixels_per_point(),
-                        screen_size_px: [
-                            screen_size_in_pixels.0.round() as _,
-                            screen_size_in_pixels.1.round() as _,
-                        ],
+                    let Some(clip_aabb) =
+                        clip_aabb(clipped.clip_rect, pixels_per_point, framebuffer_size)
+                    else {
+                        // Completely clipped
+                        continue;
+                    };
+
+                    // The callback rect in framebuffer pixels, possibly past the framebuffer
+                    let rect = callback.rect * pixels_per_point;
+                    let rect = Aabb2 {
+                        min: vec2(rect.min.x, screen_size_in_pixels.y - rect.max.y),
+                        max: vec2(rect.max.x, screen_size_in_pixels.y - rect.min.y),
+                    }
+                    .map(|x| x.round());
+
+                    // Only render the part that can be seen
+                    let clip = clip_aabb.map(|x| x as f32);
+                    let visible = Aabb2 {
+                        min: vec2(rect.min.x.max(clip.min.x), rect.min.y.max(clip.min.y)),
+                        max: vec2(rect.max.x.min(clip.max.x), rect.max.y.min(clip.max.y)),
+                    };
+                    if visible.min.x >= visible.max.x || visible.min.y >= visible.max.y {
+                        continue;
+                    }
+                    let camera = CallbackCamera {
+                        size: rect.max - rect.min,
+                        visible: Aabb2 {
+                            min: visible.min - rect.min,
+                            max: visible.max - rect.min,
+                        },
                     };
 
-                    if let Some(callback) = callback.callback.downcast_ref::<CallbackFn>() {
-                        (callback.f)(info, framebuffer);
-                    } else {
+                    // A quad to draw the rendered callback with
+                    let vertex = |a_pos: vec2<f32>, a_vt: vec2<f32>| draw2d::TexturedVertex {
+                        a_pos,
+                        a_vt,
+                        a_color: Rgba::WHITE,
+                    };
+                    let bottom_left = || vertex(visible.min, vec2(0.0, 0.0));
+                    let top_right = || vertex(visible.max, vec2(1.0, 1.0));
+                    let start = self.vertices.len();
+                    self.vertices.extend([
+                        bottom_left(),
+                        vertex(vec2(visible.max.x, visible.min.y), vec2(1.0, 0.0)),
+                        top_right(),
+                        bottom_left(),
+                        top_right(),
+                        vertex(vec2(visible.min.x, visible.max.y), vec2(0.0, 1.0)),
+                    ]);
+                    let end = self.vertices.len();
+
+                    jobs.push(PaintJob::Callback {
+                        callback,
+                        clip_rect: clipped.clip_rect,
+                        clip_aabb,
+                        camera,
+                        vertices: start..end,
+                    });
+                }
+            }
+        }
+
+        // Render
+        let mut callback_index = 0;
+        for job in jobs {
+            match job {
+                PaintJob::Batch(batch) => {
+                    let user_texture;
+                    let texture = match batch.texture_id {
+                        egui::TextureId::Managed(id) => {
+                            match self.textures.get(&batch.texture_id) {
+                                Some(texture) => texture,
+                                None => {
+                                    log::error!("egui texture {id:?} not found");
+                                    continue;
+                                }
+                            }
+                        }
+                        egui::TextureId::User(id) => {
+                            match self.user_textures.get(batch.texture_id) {
+                                Some(texture) => {
+                                    user_texture = texture;
+                                    &*user_texture
+                                }
+                                None => {
+                                    log::error!("User texture {id} not found");
+                                    continue;
+                                }
+                            }
+                        }
+                    };
+                    self.draw_vertices(framebuffer, texture, batch.clip_aabb, batch.vertices);
+                }
+                PaintJob::Callback {
+                    callback,
+                    clip_rect,
+                    clip_aabb,
+                    camera,
+                    vertices,
+                } => {
+                    let Some(callback_fn) = callback.callback.downcast_ref::<CallbackFn>() else {
                         log::warn!("Unsupported render callback. Expected geng_egui::CallbackFn");
+                        continue;
+                    };
+                    let context = CallbackContext {
+                        geng: &self.geng,
+                        info: egui::PaintCallbackInfo {
+                            viewport: callback.rect,
+                            clip_rect,
+                            pixels_per_point,
+                            screen_size_px: [
+                                screen_size_in_pixels.0.round() as _,
+                                screen_size_in_pixels.1.round() as _,
+                            ],
+                        },
+                        camera,
+                    };
+
+                    // Render the visible part of the callback into its own texture
+                    let size = (camera.visible.max - camera.visible.min).map(|x| x as usize);
+                    if callback_index == self.callback_targets.len() {
+                        self.callback_targets
+                            .push(CallbackTarget::new(self.geng.ugli(), size));
+                    }
+                    let target = &mut self.callback_targets[callback_index];
+                    callback_index += 1;
+                    if target.texture.size() != size {
+                        *target = CallbackTarget::new(self.geng.ugli(), size);
                     }
+                    {
+                        let mut callback_framebuffer = ugli::Framebuffer::new(
+                            self.geng.ugli(),
+                            ugli::ColorAttachment::Texture(&mut target.texture),
+                            ugli::DepthAttachment::Renderbuffer(&mut target.depth_buffer),
+                        );
+                        ugli::clear(
+                            &mut callback_framebuffer,
+                            Some(Rgba::TRANSPARENT_BLACK),
+                            Some(1.0),
+                            None,
+                        );
+                        let mut f = callback_fn.f.lock().unwrap();
+                        f(&context, &mut callback_framebuffer);
+                    }
+
+                    let texture = &self.callback_targets[callback_index - 1].texture;
+                    self.draw_vertices(framebuffer, texture, clip_aabb, vertices);
                 }
             }
         }
+        // Free render targets of callbacks that are gone
+        self.callback_targets.truncate(callback_index);
     }
 
-    fn paint_job(
-        &mut self,
+    fn draw_vertices(
+        &self,
         framebuffer: &mut ugli::Framebuffer,
-        clip_rect: egui::Rect,
-        mesh: egui::epaint::Mesh,
+        texture: &ugli::Texture,
+        clip_aabb: Aabb2<usize>,
+        vertices: std::ops::Range<usize>,
     ) {
-        let framebuffer_size = framebuffer.size().map(|x| x as f32);
-
-        // Convert egui clip_rect to geng clip_aabb
-        let clip_aabb = Aabb2::from_corners(
-            pos_to_vec(clip_rect.min, framebuffer_size.y),
-            pos_to_vec(clip_rect.max, framebuffer_size.y),
-        )
-        .map(|x| x as usize);
-
-        // Get font texture
-        let texture = match mesh.texture_id {
-            egui::TextureId::Managed(id) => match self.textures.get(&mesh.texture_id) {
-                Some(texture) => texture,
-                None => {
-                    log::error!("egui texture {id:?} not found");
-                    return;
-                }
-            },
-            egui::TextureId::User(_id) => todo!(),
-        };
-
-        // Convert egui vertices to geng vertices
-        let vertex_shift = clip_aabb.bottom_left().map(|x| x as f32);
-        let vertices: Vec<_> = mesh
-            .indices
-            .into_iter()
-            .map(|i| {
-                let mut vertex = textured_vertex(mesh.vertices[i as usize], framebuffer_size.y);
-                vertex.a_pos -= vertex_shift; // Because mask is applied relative to the origin
-                vertex
-            })
-            .collect();
+        let framebuffer_size = framebuffer.size();
 
         // Render triangles
+        let clip_aabb = clip_aabb.map(|x| x as f32);
         ugli::draw(
             framebuffer,
             &self.textured_program,
             ugli::DrawMode::Triangles,
-            &ugli::VertexBuffer::new_dynamic(self.geng.ugli(), vertices),
+            self.vertices.slice(vertices),
             (
                 ugli::uniforms! {
                     u_color: Rgba::WHITE,
                     u_texture: texture,
-                    u_framebuffer_size: clip_aabb.size(),
+                    u_framebuffer_size: framebuffer_size,
                     u_model_matrix: mat3::identity(),
+                    u_clip_min: clip_aabb.min,
+                    u_clip_max: clip_aabb.max,
+                    u_linear_output: if self.linear_output { 1.0 } else { 0.0 },
                 },
-                geng::PixelPerfectCamera.uniforms(clip_aabb.size().map(|x| x as f32)),
+                geng::PixelPerfectCamera.uniforms(framebuffer_size.map(|x| x as f32)),
             ),
             ugli::DrawParameters {
-                blend_mode: Some(ugli::BlendMode::straight_alpha()),
-                viewport: Some(clip_aabb),
+                blend_mode: Some(ugli::BlendMode::premultiplied_alpha()),
                 ..default()
             },
         );
@@ -161,17 +383,12 @@ impl Painter {
         let [w, h] = delta.image.size();
         let sub_size = vec2(w, h);
 
-        let filter = match delta.options.magnification {
-            egui::TextureFilter::Nearest => ugli::Filter::Nearest,
-            egui::TextureFilter::Linear => ugli::Filter::Linear,
-        };
-
         if let Some([x, y]) = delta.pos {
             let sub_pos = vec2(x, y);
 
             // Partial update
             if let Some(texture) = self.textures.get_mut(&tex_id) {
-                texture.set_filter(filter);
+                apply_texture_options(texture, delta.options);
 
                 match &delta.image {
                     egui::ImageData::Color(image) => {
@@ -225,7 +442,7 @@ impl Painter {
                     .expect("failed to convert to an image");
                     let mut texture = ugli::Texture::from_image_image(self.geng.ugli(), image);
                     // let mut texture = ugli::Texture::new_with(
-                    texture.set_filter(filter);
+                    apply_texture_options(&mut texture, delta.options);
                     self.textures.insert(tex_id, texture);
                 }
                 egui::ImageData::Font(image) => {
@@ -246,7 +463,7 @@ impl Painter {
                     )
                     .expect("failed to convert to an image");
                     let mut texture = ugli::Texture::from_image_image(self.geng.ugli(), image);
-                    texture.set_filter(filter);
+                    apply_texture_options(&mut texture, delta.options);
                     self.textures.insert(tex_id, texture);
                 }
             }
@@ -258,9 +475,61 @@ impl Painter {
     }
 }
 
-fn textured_vertex(egui_vertex: egui::epaint::Vertex, height: f32) -> draw2d::TexturedVertex {
+/// Applies egui texture options to a ugli texture.
+///
+/// No mipmaps are generated: egui 0.28's [egui::TextureOptions] has no way to request them.
+fn apply_texture_options(texture: &mut ugli::Texture, options: egui::TextureOptions) {
+    // ugli uses the same filter for magnification and minification.
+    // If they differ, prefer linear filtering to avoid shimmering when downscaling.
+    let filter = match (options.magnification, options.minification) {
+        (egui::TextureFilter::Nearest, egui::TextureFilter::Nearest) => ugli::Filter::Nearest,
+        _ => ugli::Filter::Linear,
+    };
+    texture.set_filter(filter);
+
+    let wrap_mode = match options.wrap_mode {
+        egui::TextureWrapMode::ClampToEdge => ugli::WrapMode::Clamp,
+        egui::TextureWrapMode::Repeat => ugli::WrapMode::Repeat,
+        egui::TextureWrapMode::MirroredRepeat => {
+            log::warn!("Mirrored repeat is not supported, falling back to repeat");
+            ugli::WrapMode::Repeat
+        }
+    };
+    texture.set_wrap_mode(wrap_mode);
+}
+
+/// Converts an egui clip rect (in points, top-left origin) to a framebuffer area
+/// (in pixels, bottom-left origin), rounded to whole pixels and clamped to the framebuffer.
+/// Returns `None` if nothing would be visible.
+fn clip_aabb(
+    clip_rect: egui::Rect,
+    pixels_per_point: f32,
+    framebuffer_size: vec2<usize>,
+) -> Option<Aabb2<usize>> {
+    let to_pixels = |points: f32, size: usize| -> usize {
+        (points * pixels_per_point).round().clamp(0.0, size as f32) as usize
+    };
+    let min_x = to_pixels(clip_rect.min.x, framebuffer_size.x);
+    let max_x = to_pixels(clip_rect.max.x, framebuffer_size.x);
+    let top = to_pixels(clip_rect.min.y, framebuffer_size.y);
+    let bottom = to_pixels(clip_rect.max.y, framebuffer_size.y);
+    if min_x >= max_x || top >= bottom {
+        return None;
+    }
+    Some(Aabb2 {
+        min: vec2(min_x, framebuffer_size.y - bottom),
+        max: vec2(max_x, framebuffer_size.y - top),
+    })
+}
+
+/// Converts an egui vertex (in points) to a geng vertex (in pixels).
+fn textured_vertex(
+    egui_vertex: egui::epaint::Vertex,
+    height: f32,
+    pixels_per_point: f32,
+) -> draw2d::TexturedVertex {
     draw2d::TexturedVertex {
-        a_pos: pos_to_vec(egui_vertex.pos, height),
+        a_pos: pos_to_vec(egui_vertex.pos * pixels_per_point, height),
         a_vt: pos_to_vec(egui_vertex.uv, 1.0),
         a_color: Rgba::new(
             egui_vertex.color.r(),
@@ -271,3 +540,128 @@ fn textured_vertex(egui_vertex: egui::epaint::Vertex, height: f32) -> draw2d::Te
         .convert(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAMEBUFFER_SIZE: vec2<usize> = vec2(100, 50);
+
+    fn rect(min_x: f32, min_y: f32, max_x: f32, max_y: f32) -> egui::Rect {
+        egui::Rect::from_min_max(egui::pos2(min_x, min_y), egui::pos2(max_x, max_y))
+    }
+
+    fn aabb(min_x: usize, min_y: usize, max_x: usize, max_y: usize) -> Option<Aabb2<usize>> {
+        Some(Aabb2 {
+            min: vec2(min_x, min_y),
+            max: vec2(max_x, max_y),
+        })
+    }
+
+    #[test]
+    fn clip_inside() {
+        assert_eq!(
+            clip_aabb(rect(10.0, 10.0, 20.0, 20.0), 1.0, FRAMEBUFFER_SIZE),
+            aabb(10, 30, 20, 40)
+        );
+    }
+
+    #[test]
+    fn clip_past_edges() {
+        // Left
+        assert_eq!(
+            clip_aabb(rect(-10.0, 10.0, 20.0, 20.0), 1.0, FRAMEBUFFER_SIZE),
+            aabb(0, 30, 20, 40)
+        );
+        // Right
+        assert_eq!(
+            clip_aabb(rect(80.0, 10.0, 150.0, 20.0), 1.0, FRAMEBUFFER_SIZE),
+            aabb(80, 30, 100, 40)
+        );
+        // Top
+        assert_eq!(
+            clip_aabb(rect(10.0, -10.0, 20.0, 20.0), 1.0, FRAMEBUFFER_SIZE),
+            aabb(10, 30, 20, 50)
+        );
+        // Bottom
+        assert_eq!(
+            clip_aabb(rect(10.0, 40.0, 20.0, 80.0), 1.0, FRAMEBUFFER_SIZE),
+            aabb(10, 0, 20, 10)
+        );
+        // All at once
+        assert_eq!(
+            clip_aabb(rect(-10.0, -10.0, 200.0, 200.0), 1.0, FRAMEBUFFER_SIZE),
+            aabb(0, 0, 100, 50)
+        );
+    }
+
+    #[test]
+    fn clip_off_screen() {
+        for clip_rect in [
+            rect(-30.0, 10.0, -10.0, 20.0),
+            rect(120.0, 10.0, 150.0, 20.0),
+            rect(10.0, -20.0, 20.0, -5.0),
+            rect(10.0, 60.0, 20.0, 70.0),
+            // Touching the edge from outside
+            rect(100.0, 10.0, 120.0, 20.0),
+            rect(10.0, -10.0, 20.0, 0.0),
+        ] {
+            assert_eq!(
+                clip_aabb(clip_rect, 1.0, FRAMEBUFFER_SIZE),
+                None,
+                "{clip_rect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn clip_rounding() {
+        assert_eq!(
+            clip_aabb(rect(10.4, 10.5, 20.6, 19.4), 1.0, FRAMEBUFFER_SIZE),
+            aabb(10, 31, 21, 39)
+        );
+        // Nothing left after rounding
+        assert_eq!(
+            clip_aabb(rect(10.1, 10.0, 10.3, 20.0), 1.0, FRAMEBUFFER_SIZE),
+            None
+        );
+    }
+
+    #[test]
+    fn clip_pixels_per_point() {
+        assert_eq!(
+            clip_aabb(rect(5.0, 5.0, 20.0, 15.0), 2.0, FRAMEBUFFER_SIZE),
+            aabb(10, 20, 40, 40)
+        );
+        // Past the right and bottom edges only after scaling
+        assert_eq!(
+            clip_aabb(rect(40.0, 20.0, 60.0, 30.0), 2.0, FRAMEBUFFER_SIZE),
+            aabb(80, 0, 100, 10)
+        );
+        assert_eq!(
+            clip_aabb(rect(10.2, 10.0, 20.0, 20.0), 1.5, FRAMEBUFFER_SIZE),
+            aabb(15, 20, 30, 35)
+        );
+    }
+
+    #[test]
+    fn callback_camera_maps_visible_part() {
+        use geng::AbstractCamera2d;
+
+        let camera = CallbackCamera {
+            size: vec2(100.0, 200.0),
+            visible: Aabb2 {
+                min: vec2(20.0, 50.0),
+                max: vec2(60.0, 150.0),
+            },
+        };
+        let matrix = camera.projection_matrix(vec2(40.0, 100.0)) * camera.view_matrix();
+        let assert_projects = |pos: vec2<f32>, expected: vec2<f32>| {
+            let projected = (matrix * pos.extend(1.0)).xy();
+            assert!((projected - expected).len() < 1e-5, "{projected:?}");
+        };
+        assert_projects(vec2(20.0, 50.0), vec2(-1.0, -1.0));
+        assert_projects(vec2(60.0, 150.0), vec2(1.0, 1.0));
+        assert_projects(vec2(40.0, 100.0), vec2(0.0, 0.0));
+    }
+}
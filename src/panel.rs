@@ -0,0 +1,132 @@
+use super::*;
+
+/// An egui context rendered into an offscreen texture instead of the screen,
+/// e.g. to place interactive panels on objects in the game world.
+///
+/// The texture contains premultiplied colors.
+pub struct EguiPanel {
+    egui: EguiGeng,
+    texture: ugli::Texture,
+    pointer_uv: Option<vec2<f32>>,
+}
+
+impl EguiPanel {
+    pub fn new(geng: &Geng, size: vec2<usize>) -> Self {
+        let mut egui = EguiGeng::new(geng);
+        // The window cursor should only be changed while pointing at the panel's ui
+        egui.set_cursor_passthrough(true);
+        Self {
+            texture: ugli::Texture::new_uninitialized(geng.ugli(), size),
+            egui,
+            pointer_uv: None,
+        }
+    }
+
+    /// Use to call ui methods: open windows, panels, etc.
+    pub fn get_context(&self) -> &egui::Context {
+        self.egui.get_context()
+    }
+
+    /// Access the underlying integration, e.g. to change its settings.
+    pub fn egui_mut(&mut self) -> &mut EguiGeng {
+        &mut self.egui
+    }
+
+    /// The texture the ui is rendered into.
+    pub fn texture(&self) -> &ugli::Texture {
+        &self.texture
+    }
+
+    pub fn size(&self) -> vec2<usize> {
+        self.texture.size()
+    }
+
+    /// Change the size of the texture. Its contents are lost until the next [Self::draw].
+    pub fn resize(&mut self, size: vec2<usize>) {
+        if self.texture.size() != size {
+            self.texture = ugli::Texture::new_uninitialized(self.egui.geng.ugli(), size);
+        }
+    }
+
+    /// See [EguiGeng::begin_frame].
+    pub fn begin_frame(&mut self) {
+        self.egui.begin_frame();
+    }
+
+    /// See [EguiGeng::end_frame].
+    pub fn end_frame(&mut self) {
+        self.egui.end_frame();
+    }
+
+    /// Render the ui into the texture. Call after [Self::end_frame].
+    pub fn draw(&mut self) {
+        let mut framebuffer = ugli::Framebuffer::new_color(
+            self.egui.geng.ugli(),
+            ugli::ColorAttachment::Texture(&mut self.texture),
+        );
+        ugli::clear(&mut framebuffer, Some(Rgba::TRANSPARENT_BLACK), None, None);
+        self.egui.draw(&mut framebuffer);
+    }
+
+    /// Set where the pointer hits the panel, in texture coordinates
+    /// (`0..1` with the origin at the bottom-left corner), or `None` if it misses.
+    /// See [panel_uv] to compute it from a ray in the world.
+    pub fn set_pointer_uv(&mut self, uv: Option<vec2<f32>>) {
+        if uv == self.pointer_uv {
+            return;
+        }
+        self.pointer_uv = uv;
+        match uv {
+            Some(uv) => {
+                let position = uv.map(f64::from) * self.size().map(|x| x as f64);
+                self.egui.handle_event(geng::Event::CursorMove { position });
+            }
+            None => self.egui.pointer_gone(),
+        }
+    }
+
+    /// Forward an event to the panel. Window cursor movement is ignored,
+    /// use [Self::set_pointer_uv] to move the pointer instead.
+    /// Mouse presses and wheel events are ignored while the pointer is outside the panel.
+    ///
+    /// Returns whether the panel's egui wants the event.
+    pub fn handle_event(&mut self, event: geng::Event) -> bool {
+        match event {
+            geng::Event::CursorMove { .. } => return false,
+            geng::Event::MousePress { .. } | geng::Event::Wheel { .. }
+                if self.pointer_uv.is_none() =>
+            {
+                return false
+            }
+            _ => {}
+        }
+        self.egui.handle_event(event)
+    }
+}
+
+/// Finds where a ray hits a panel placed in the world.
+///
+/// `transform` maps the unit square (`0..1` on the x and y axes, at z = 0) onto the panel in the world.
+/// Returns the hit point in texture coordinates, or `None` if the ray misses the panel.
+pub fn panel_uv(
+    transform: mat4<f32>,
+    ray_origin: vec3<f32>,
+    ray_dir: vec3<f32>,
+) -> Option<vec2<f32>> {
+    let inverse = transform.inverse();
+    let origin = inverse * ray_origin.extend(1.0);
+    let origin = origin.xyz() / origin.w;
+    let dir = (inverse * ray_dir.extend(0.0)).xyz();
+    if dir.z.abs() < 1e-6 {
+        // Parallel to the panel
+        return None;
+    }
+    let t = -origin.z / dir.z;
+    if t < 0.0 {
+        return None;
+    }
+    let hit = origin + dir * t;
+    let uv = hit.xy();
+    let inside = (0.0..=1.0).contains(&uv.x) && (0.0..=1.0).contains(&uv.y);
+    inside.then_some(uv)
+}
@@ -0,0 +1,84 @@
+use super::*;
+
+/// A [geng::State] with egui ui, to be wrapped in [WithEgui].
+pub trait EguiState: geng::State {
+    /// Add the ui: open windows, panels, etc.
+    /// Called every frame after [geng::State::update].
+    fn egui(&mut self, ctx: &egui::Context);
+}
+
+/// Runs the egui frame lifecycle around a [geng::State].
+///
+/// Events are passed to the inner state only if egui did not want them (see [EguiGeng::wants_event]),
+/// and the ui is drawn on top of whatever the inner state draws.
+pub struct WithEgui<S> {
+    egui: EguiGeng,
+    inner: S,
+}
+
+impl<S: EguiState> WithEgui<S> {
+    pub fn new(geng: &Geng, inner: S) -> Self {
+        Self {
+            egui: EguiGeng::new(geng),
+            inner,
+        }
+    }
+
+    pub fn egui(&self) -> &EguiGeng {
+        &self.egui
+    }
+
+    pub fn egui_mut(&mut self) -> &mut EguiGeng {
+        &mut self.egui
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: EguiState> geng::State for WithEgui<S> {
+    fn update(&mut self, delta_time: f64) {
+        self.inner.update(delta_time);
+
+        self.egui.begin_frame();
+        self.inner.egui(self.egui.get_context());
+        self.egui.end_frame();
+    }
+
+    fn fixed_update(&mut self, delta_time: f64) {
+        self.inner.fixed_update(delta_time);
+    }
+
+    fn draw(&mut self, framebuffer: &mut ugli::Framebuffer) {
+        self.inner.draw(framebuffer);
+        self.egui.draw(framebuffer);
+    }
+
+    fn handle_event(&mut self, event: geng::Event) {
+        if !self.egui.handle_event(event.clone()) {
+            self.inner.handle_event(event);
+        }
+    }
+
+    fn transition(&mut self) -> Option<geng::state::Transition> {
+        let transition = self.inner.transition();
+        if transition.is_some() {
+            // Another state takes over, so this one stops receiving input and drawing
+            self.egui.suspend();
+        }
+        transition
+    }
+
+    fn ui<'a>(&'a mut self, cx: &'a geng::ui::Controller) -> Box<dyn geng::ui::Widget + 'a> {
+        self.inner.ui(cx)
+    }
+}
@@ -22,8 +22,30 @@ void main() {
 #ifdef FRAGMENT_SHADER
 uniform vec4 u_color;
 uniform sampler2D u_texture;
+// Clip rect in framebuffer pixels
+uniform vec2 u_clip_min;
+uniform vec2 u_clip_max;
+// Whether the framebuffer expects linear colors instead of sRGB gamma
+uniform float u_linear_output;
+
+vec3 linear_from_gamma(vec3 srgb) {
+    bvec3 cutoff = lessThan(srgb, vec3(0.04045));
+    vec3 lower = srgb / vec3(12.92);
+    vec3 higher = pow((srgb + vec3(0.055)) / vec3(1.055), vec3(2.4));
+    return mix(higher, lower, vec3(cutoff));
+}
 
 void main() {
-    gl_FragColor = v_color * u_color * texture2D(u_texture, v_vt);
+    if (gl_FragCoord.x < u_clip_min.x || gl_FragCoord.x > u_clip_max.x ||
+        gl_FragCoord.y < u_clip_min.y || gl_FragCoord.y > u_clip_max.y) {
+        discard;
+    }
+    // Colors and textures are premultiplied and in gamma space.
+    // Multiplying them in gamma space is what egui expects, and is needed for text to look right.
+    vec4 color = v_color * u_color * texture2D(u_texture, v_vt);
+    if (u_linear_output > 0.5) {
+        color.rgb = linear_from_gamma(color.rgb);
+    }
+    gl_FragColor = color;
 }
 #endif
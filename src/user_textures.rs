@@ -0,0 +1,60 @@
+use super::*;
+
+/// Textures owned by the game rather than egui, referred to by [egui::TextureId::User].
+///
+/// This is a cheap handle, clones refer to the same registry.
+#[derive(Clone, Default)]
+pub struct UserTextures {
+    inner: Rc<RefCell<UserTexturesInner>>,
+}
+
+#[derive(Default)]
+struct UserTexturesInner {
+    next_id: u64,
+    textures: HashMap<u64, Rc<ugli::Texture>>,
+}
+
+impl UserTextures {
+    /// Register a texture so that it can be used in egui (e.g. by [egui::Image]).
+    /// Like the rest of egui, the texture is expected to use premultiplied alpha.
+    pub fn register(&self, texture: Rc<ugli::Texture>) -> egui::TextureId {
+        let mut inner = self.inner.borrow_mut();
+        let id = inner.next_id;
+        inner.next_id += 1;
+        inner.textures.insert(id, texture);
+        egui::TextureId::User(id)
+    }
+
+    /// Replace the texture behind a registered id, returning the previous one.
+    pub fn replace(
+        &self,
+        id: egui::TextureId,
+        texture: Rc<ugli::Texture>,
+    ) -> Option<Rc<ugli::Texture>> {
+        let egui::TextureId::User(id) = id else {
+            log::error!("Cannot replace egui managed texture {id:?}");
+            return None;
+        };
+        match self.inner.borrow_mut().textures.get_mut(&id) {
+            Some(old) => Some(std::mem::replace(old, texture)),
+            None => {
+                log::error!("User texture {id} not found");
+                None
+            }
+        }
+    }
+
+    /// Stop using the texture. The id must not be used afterwards.
+    pub fn free(&self, id: egui::TextureId) {
+        if let egui::TextureId::User(id) = id {
+            self.inner.borrow_mut().textures.remove(&id);
+        }
+    }
+
+    pub fn get(&self, id: egui::TextureId) -> Option<Rc<ugli::Texture>> {
+        match id {
+            egui::TextureId::User(id) => self.inner.borrow().textures.get(&id).cloned(),
+            egui::TextureId::Managed(_) => None,
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use super::*;
+
+/// A widget that shows a geng render inside egui, e.g. a scene preview in an editor.
+///
+/// Call [Self::show] during the ui logic to place the widget, then [Self::draw] to render into it
+/// at the widget's size in pixels. Input received by the widget is available from [Self::take_events],
+/// with positions relative to the widget (in pixels, with the origin at its bottom-left corner).
+pub struct Viewport {
+    geng: Geng,
+    textures: UserTextures,
+    texture_id: egui::TextureId,
+    /// The texture not currently shown, reused for the next [Self::draw].
+    back_texture: Option<ugli::Texture>,
+    /// Depth buffer for [Self::draw], with its size.
+    depth_buffer: Option<(ugli::Renderbuffer<ugli::DepthComponent>, vec2<usize>)>,
+    /// Size of the widget in pixels as of the last [Self::show].
+    size: vec2<usize>,
+    events: Vec<geng::Event>,
+}
+
+impl Viewport {
+    pub fn new(egui: &EguiGeng) -> Self {
+        let geng = egui.geng.clone();
+        let textures = egui.user_textures().clone();
+        let texture = ugli::Texture::new_uninitialized(geng.ugli(), vec2(1, 1));
+        let texture_id = textures.register(Rc::new(texture));
+        Self {
+            geng,
+            textures,
+            texture_id,
+            back_texture: None,
+            depth_buffer: None,
+            size: vec2(1, 1),
+            events: Vec::new(),
+        }
+    }
+
+    /// Size of the widget in pixels.
+    pub fn size(&self) -> vec2<usize> {
+        self.size
+    }
+
+    /// Show the viewport taking all the available space.
+    pub fn show(&mut self, ui: &mut egui::Ui) -> egui::Response {
+        self.show_sized(ui, ui.available_size())
+    }
+
+    /// Show the viewport with the given size (in points).
+    pub fn show_sized(&mut self, ui: &mut egui::Ui, size: egui::Vec2) -> egui::Response {
+        let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click_and_drag());
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        let pixel_size = rect.size() * pixels_per_point;
+        self.size = vec2(pixel_size.x, pixel_size.y).map(|x| (x.round() as usize).max(1));
+
+        if response.clicked() || response.drag_started() {
+            response.request_focus();
+        }
+
+        ui.painter().image(
+            self.texture_id,
+            rect,
+            egui::Rect::from_min_max(egui::Pos2::ZERO, egui::pos2(1.0, 1.0)),
+            egui::Color32::WHITE,
+        );
+
+        let pointer_active =
+            response.hovered() || response.is_pointer_button_down_on() || response.drag_stopped();
+        let keyboard_active = response.has_focus();
+        if pointer_active || keyboard_active {
+            let to_position = |pos: egui::Pos2| {
+                let pos = (pos - rect.min) * pixels_per_point;
+                vec2(pos.x, rect.height() * pixels_per_point - pos.y).map(f64::from)
+            };
+            let events = ui.input(|input| input.events.clone());
+            for event in events {
+                match event {
+                    egui::Event::PointerMoved(pos) if pointer_active => {
+                        self.events.push(geng::Event::CursorMove {
+                            position: to_position(pos),
+                        });
+                    }
+                    egui::Event::PointerButton {
+                        pos,
+                        button,
+                        pressed,
+                        ..
+                    } if pointer_active => {
+                        let Some(button) = geng_button(button) else {
+                            continue;
+                        };
+                        self.events.push(geng::Event::CursorMove {
+                            position: to_position(pos),
+                        });
+                        self.events.push(if pressed {
+                            geng::Event::MousePress { button }
+                        } else {
+                            geng::Event::MouseRelease { button }
+                        });
+                    }
+                    egui::Event::MouseWheel { delta, .. } if response.hovered() => {
+                        self.events.push(geng::Event::Wheel {
+                            delta: delta.y as f64,
+                        });
+                    }
+                    egui::Event::Key { key, pressed, .. } if keyboard_active => {
+                        let Some(key) = geng_key(key) else {
+                            continue;
+                        };
+                        self.events.push(if pressed {
+                            geng::Event::KeyPress { key }
+                        } else {
+                            geng::Event::KeyRelease { key }
+                        });
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        response
+    }
+
+    /// Input received by the widget since the last call.
+    pub fn take_events(&mut self) -> Vec<geng::Event> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Render into the viewport. Call after [Self::show] and before [EguiGeng::draw].
+    /// The framebuffer has a depth buffer, so 3D scenes can be drawn with depth testing.
+    pub fn draw(&mut self, f: impl FnOnce(&mut ugli::Framebuffer)) {
+        let mut texture = match self.back_texture.take() {
+            Some(texture) if texture.size() == self.size => texture,
+            _ => ugli::Texture::new_uninitialized(self.geng.ugli(), self.size),
+        };
+        if !matches!(self.depth_buffer, Some((_, size)) if size == self.size) {
+            let depth_buffer = ugli::Renderbuffer::new(self.geng.ugli(), self.size);
+            self.depth_buffer = Some((depth_buffer, self.size));
+        }
+        let (depth_buffer, _) = self.depth_buffer.as_mut().unwrap();
+        {
+            let mut framebuffer = ugli::Framebuffer::new(
+                self.geng.ugli(),
+                ugli::ColorAttachment::Texture(&mut texture),
+                ugli::DepthAttachment::Renderbuffer(depth_buffer),
+            );
+            f(&mut framebuffer);
+        }
+
+        // Show the new texture and keep the old one around for the next frame
+        let front_texture = self.textures.replace(self.texture_id, Rc::new(texture));
+        self.back_texture = front_texture.and_then(|texture| Rc::try_unwrap(texture).ok());
+    }
+}
+
+impl Drop for Viewport {
+    fn drop(&mut self) {
+        self.textures.free(self.texture_id);
+    }
+}
+
+fn geng_button(egui_button: egui::PointerButton) -> Option<geng::MouseButton> {
+    match egui_button {
+        egui::PointerButton::Primary => Some(geng::MouseButton::Left),
+        egui::PointerButton::Middle => Some(geng::MouseButton::Middle),
+        egui::PointerButton::Secondary => Some(geng::MouseButton::Right),
+        egui::PointerButton::Extra1 | egui::PointerButton::Extra2 => None,
+    }
+}